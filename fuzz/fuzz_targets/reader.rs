@@ -0,0 +1,51 @@
+//! Reading arbitrary input must never panic, only return errors.
+//!
+//! Run with `cargo +nightly fuzz run reader` from the repository root.
+#![no_main]
+
+use std::io::Read;
+
+use libfuzzer_sys::fuzz_target;
+use tifiles::read::{self, PictureModel, VariableIter};
+use tifiles::{Reader, VariableType};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read::peek_header(&mut &data[..]);
+    for file in VariableIter::new(data) {
+        if file.is_err() {
+            break;
+        }
+    }
+    let _ = read::flash::FlashReader::new(data);
+    let _ = Reader::from_slice(data);
+    if let Ok(mut reader) = Reader::new_lenient(data) {
+        let _ = reader.read_all_data();
+        let _ = reader.finish();
+    }
+
+    let mut reader = match Reader::new(data) {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+    let ty = reader.ty();
+    let _ = (reader.name_str(), reader.comment_str(), reader.equation_slot());
+    if ty == VariableType::Group {
+        let _ = reader.group_members();
+    }
+
+    let mut contents = Vec::new();
+    if reader.read_to_end(&mut contents).is_ok() {
+        let _ = reader.finish();
+    }
+
+    // The decoders see the data of any type, not only the one they're meant for
+    let _ = read::decode_real(&contents);
+    let _ = read::decode_complex(&contents);
+    let _ = read::decode_list(&contents);
+    let _ = read::decode_complex_list(&contents);
+    let _ = read::decode_matrix(&contents);
+    let _ = read::decode_picture(&contents, PictureModel::Monochrome);
+    let _ = read::decode_picture(&contents, PictureModel::Color);
+    let _ = read::decode_string(&contents);
+    let _ = read::tokens::detokenize(&contents);
+});
@@ -0,0 +1,184 @@
+//! Async readers and writers for tokio
+//!
+//! [`AsyncReader`] and [`AsyncWriter`] are the async counterparts of [`Reader`] and
+//! [`BufferedWriter`]. Because variable files are small (never more than 64 KiB), these buffer
+//! entire files in memory and use the synchronous implementations to parse and generate them,
+//! so the results are exactly the same as for synchronous IO.
+
+use std::io::{Cursor, Read, Result as IoResult, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+use crate::read::{check_signature, FinishError};
+use crate::write::BufferedWriter;
+use crate::{Reader, VariableType};
+
+/// Reads TI variable files from an [`AsyncRead`].
+///
+/// The entire file is read when the reader is constructed. Its contents can then be read via the
+/// [`AsyncRead`] impl, and the file's metadata inspected with [`get_ref`](AsyncReader::get_ref).
+pub struct AsyncReader<R> {
+    inner: Reader<Cursor<Vec<u8>>>,
+    r: R,
+}
+
+impl<R: AsyncRead + Unpin> AsyncReader<R> {
+    /// Read a variable file from the provided input.
+    ///
+    /// Returns the same errors as [`Reader::new`].
+    pub async fn new(mut r: R) -> IoResult<Self> {
+        // Signature, comment and data section length
+        let mut data = vec![0u8; 55];
+        r.read_exact(&mut data).await?;
+        check_signature(data[..11].try_into().unwrap())?;
+
+        // Data section and checksum
+        let data_section_len = u16::from_le_bytes([data[53], data[54]]) as usize;
+        data.resize(55 + data_section_len + 2, 0);
+        r.read_exact(&mut data[55..]).await?;
+
+        Ok(AsyncReader {
+            inner: Reader::new(Cursor::new(data))?,
+            r,
+        })
+    }
+
+    /// Get the synchronous reader for the buffered file, which provides access to the file's
+    /// metadata.
+    pub fn get_ref(&self) -> &Reader<Cursor<Vec<u8>>> {
+        &self.inner
+    }
+
+    /// Finish reading the input, dropping unread data.
+    ///
+    /// This behaves the same as [`Reader::finish`]. The entire file has already been read from
+    /// the input, so the returned input is positioned after all file data.
+    pub async fn finish(self) -> IoResult<Result<R, FinishError<R>>> {
+        let Self { inner, r } = self;
+        Ok(match inner.finish()? {
+            Ok(_) => Ok(r),
+            Err(e) => Err(e.with_reader(r)),
+        })
+    }
+}
+
+impl<R: Unpin> AsyncRead for AsyncReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        // Data is in memory, so reads can always complete immediately
+        let n = self.get_mut().inner.read(buf.initialize_unfilled())?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Writes TI variable files to an [`AsyncWrite`].
+///
+/// Like [`BufferedWriter`], data is buffered in memory and nothing is written to the output
+/// until [`close`](AsyncWriter::close) is called.
+pub struct AsyncWriter<W> {
+    w: BufferedWriter<Vec<u8>>,
+    output: W,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWriter<W> {
+    /// Open an output for writing.
+    ///
+    /// Parameters are the same as [`Writer::new`](crate::Writer::new).
+    pub fn new(output: W, ty: VariableType, name: &str, archived: bool) -> IoResult<Self> {
+        Ok(AsyncWriter {
+            w: BufferedWriter::new(Vec::new(), ty, name, archived)?,
+            output,
+        })
+    }
+
+    /// Open an output for writing, with a custom file comment.
+    ///
+    /// Parameters are the same as [`Writer::with_comment`](crate::Writer::with_comment).
+    pub fn with_comment(
+        output: W,
+        ty: VariableType,
+        name: &str,
+        archived: bool,
+        comment: &str,
+    ) -> IoResult<Self> {
+        Ok(AsyncWriter {
+            w: BufferedWriter::with_comment(Vec::new(), ty, name, archived, comment)?,
+            output,
+        })
+    }
+
+    /// Finalize the variable file, write it to the output and return the output.
+    ///
+    /// If this is not called, nothing will be written to the output.
+    pub async fn close(self) -> IoResult<W> {
+        let Self { w, mut output } = self;
+        output.write_all(&w.close()?).await?;
+        output.flush().await?;
+        Ok(output)
+    }
+}
+
+impl<W: Unpin> AsyncWrite for AsyncWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        Poll::Ready(self.get_mut().w.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        // Nothing can be written to the output until the writer is closed
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        // The file is only complete once closed, which must be done with close()
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Async IO produces exactly the same files as synchronous IO, and can read them back.
+#[test]
+fn async_round_trip_matches_sync() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let mut expected = vec![];
+    let mut writer = crate::Writer::new(
+        Cursor::new(&mut expected),
+        VariableType::AppVar,
+        "ASYNC",
+        true,
+    )
+    .unwrap();
+    writer.write_all(b"async data").unwrap();
+    writer.close().unwrap();
+
+    rt.block_on(async {
+        let mut writer = AsyncWriter::new(Vec::new(), VariableType::AppVar, "ASYNC", true).unwrap();
+        writer.write_all(b"async data").await.unwrap();
+        let actual = writer.close().await.unwrap();
+        assert_eq!(actual, expected);
+
+        let mut reader = AsyncReader::new(&*actual).await.unwrap();
+        assert_eq!(reader.get_ref().ty(), VariableType::AppVar);
+        assert_eq!(reader.get_ref().name_str(), "ASYNC");
+        let mut data = vec![];
+        reader.read_to_end(&mut data).await.unwrap();
+        assert_eq!(data, b"async data");
+        let rest = reader
+            .finish()
+            .await
+            .unwrap()
+            .expect("checksum should be valid");
+        assert!(rest.is_empty());
+    });
+}
@@ -12,7 +12,8 @@
 //! let outf = std::io::Cursor::new(Vec::new());
 //! let mut bundle = Writer::new(Kind::B84, outf);
 //!
-//! // Writes to the bundle append to the most recently started var
+//! // Writes to the bundle append to the most recently started var, and fail if there isn't one
+//! assert!(bundle.write_all(b"orphaned").is_err());
 //! bundle.start_var(VariableType::ProtectedProgram, "NOP", false)?;
 //! bundle.write_all(&[0xbb, 0x6d, 0xc9])?;
 //!
@@ -36,27 +37,90 @@
 //!    * bundle_format_version: 1
 //!    * bundle_target_device: `83CE` or `84CE`
 //!    * bundle_target_type: `CUSTOM` (presumably other values are also understood)
-//!    * bundle_comments: anything you like, apparently
+//!    * bundle_comments: anything you like, apparently (but on a single line)
 //!  * _CHECKSUM: the arithmetic sum of the CRC32 of each individual variable file's uncompressed
 //!    data (as fed into the zip writer). This file is a single line of that CRC formatted as a hex
 //!    number followed by `\r\n`.
 //!
 //! The order of zip entries appears to matter: variable files must come first, followed by METADATA
 //! and _CHECKSUM in that order.
+//!
+//! Bundles can be read back with a [`Reader`], which yields a [`crate::Reader`] for each contained
+//! variable:
+//!
+//! ```
+//! # use std::io::Read;
+//! # use tifiles::bundle::Reader;
+//! # fn doit<R: Read + std::io::Seek>(input: R) -> Result<(), Box<dyn std::error::Error>> {
+//! let mut bundle = Reader::new(input)?;
+//! bundle.validate_checksum()?;
+//!
+//! for var in bundle.variables() {
+//!     let var = var?;
+//!     println!("{:?} {:?}", var.ty(), var.name());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! To convert a bundle back into individual variable files, use [`extract_all`].
 
 use std::io::{Cursor, Read, Result as IoResult, Seek, Write};
+use std::path::{Path, PathBuf};
 use zip::result::ZipError;
 use zip::write::FileOptions;
 
-use zip::ZipWriter;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::write::WriteError;
+use crate::{Reader as VarReader, VariableType, Writer as VarWriter};
 
-use crate::{VariableType, Writer as VarWriter};
+/// Name of the zip entry containing bundle metadata.
+const METADATA: &str = "METADATA";
+/// Name of the zip entry containing the bundle checksum.
+const CHECKSUM: &str = "_CHECKSUM";
+
+/// Custom IO error variants for bundles.
+///
+/// These are returned in a `Custom` [`std::io::Error`].
+#[derive(thiserror::Error, Debug)]
+pub enum BundleError {
+    /// The bundle does not contain a required entry (METADATA or _CHECKSUM).
+    #[error("Bundle is missing required entry {0}")]
+    MissingEntry(&'static str),
+    /// A METADATA field is missing or could not be parsed.
+    #[error("Bundle metadata field {0} is missing or invalid")]
+    InvalidMetadata(&'static str),
+    /// The bundle's target device does not correspond to a known [`Kind`].
+    #[error("Bundle target device {0:?} is not recognized")]
+    UnknownDevice(String),
+    /// The _CHECKSUM entry does not match the contents of the bundle.
+    #[error("Bundle checksum was {recorded:#x} but entries summed to {computed:#x}")]
+    ChecksumMismatch { computed: u32, recorded: u32 },
+    /// Data was written to a [`Writer`] before any variable was started.
+    #[error("start_var must be called on a bundle writer before data can be written")]
+    NoActiveVar,
+    /// Too much data was written to a variable in a bundle.
+    #[error("Variable {name} would contain {size} bytes, more than a variable file can hold")]
+    TooLarge { name: String, size: usize },
+    /// A variable file name is not a valid variable name followed by the extension of a variable
+    /// type.
+    #[error("{0:?} is not a valid variable file name")]
+    InvalidEntryName(String),
+}
+
+impl From<BundleError> for std::io::Error {
+    fn from(e: BundleError) -> Self {
+        std::io::Error::other(e)
+    }
+}
 
 /// Supported bundle kinds.
 ///
 /// A bundle of a given kind has no particular affinity with a given calculator,
 /// but TI-Connect may refuse to transfer a bundle to a calculator if the bundle
 /// kind does not match the calculator.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Kind {
     /// .b83, for TI-83 Premium CE
     B83,
@@ -79,16 +143,46 @@ impl Kind {
             Kind::B84 => "84CE",
         }
     }
+
+    /// Return the bundle kind customarily associated with a file extension, ignoring case.
+    ///
+    /// This is the inverse of [`file_extension`](Kind::file_extension).
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "b83" => Some(Kind::B83),
+            "b84" => Some(Kind::B84),
+            _ => None,
+        }
+    }
+
+    /// Return the bundle kind for a target device name in bundle metadata, such as `84CE`.
+    ///
+    /// Case is ignored.
+    pub fn from_device_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "83CE" => Some(Kind::B83),
+            "84CE" => Some(Kind::B84),
+            _ => None,
+        }
+    }
 }
 
 /// Writes bundle files.
 ///
 /// A bundle contains zero or more variables, which are written using the
 /// [`Write` impl](impl std::io::Write). For each call to [`start_var`](Writer::start_var),
-/// subsequent writes will append to that variable's data.
+/// subsequent writes will append to that variable's data. Writing before any variable has been
+/// started fails with [`BundleError::NoActiveVar`].
+///
+/// Writers can be configured after creation with setters like
+/// [`set_comments`](Writer::set_comments), or all at once with a [`Builder`].
 ///
 /// Users must call [`close`](Writer::close) when done writing all variables
 /// in order to create the required metadata entries and close the archive.
+///
+/// Entries are always written in the order that TI-Connect requires: every variable, then
+/// METADATA, then _CHECKSUM. Only `close` writes the last two, and it consumes the writer so no
+/// variables can follow them.
 pub struct Writer<W>
 where
     W: Write + Seek,
@@ -96,7 +190,19 @@ where
     kind: Kind,
     zip: ZipWriter<W>,
     crc_sum: u32,
-    active_var: Option<(VarWriter<Cursor<Vec<u8>>>, String)>,
+    entries: Vec<(String, u32)>,
+    bytes_written: u64,
+    comments: String,
+    target_type: String,
+    file_options: FileOptions,
+    active_var: Option<ActiveVar>,
+}
+
+/// The variable currently being written to a bundle.
+struct ActiveVar {
+    w: VarWriter<Cursor<Vec<u8>>>,
+    /// Name of the zip entry the variable will be written to
+    name: String,
 }
 
 impl<W> Writer<W>
@@ -104,78 +210,342 @@ where
     W: Write + Seek,
 {
     pub fn new(kind: Kind, writer: W) -> Self {
+        Self::from_zip(kind, ZipWriter::new(writer))
+    }
+
+    /// Create a bundle writer that adds its entries to an existing zip writer.
+    ///
+    /// This allows the zip to be configured before the bundle is written, such as by setting an
+    /// archive comment. Entries already in the zip will precede the bundle's variables; since
+    /// TI-Connect may not expect other entries, they are best avoided in bundles meant to be sent
+    /// to a calculator.
+    ///
+    /// Use [`close_into_zip`](Writer::close_into_zip) instead of [`close`](Writer::close) to get
+    /// the zip writer back without finishing the archive.
+    pub fn from_zip(kind: Kind, zip: ZipWriter<W>) -> Self {
         Writer {
             kind,
-            zip: ZipWriter::new(writer),
+            zip,
             crc_sum: 0,
+            entries: Vec::new(),
+            bytes_written: 0,
+            comments: "Generated by tifiles-rs::bundle::Writer".into(),
+            target_type: "CUSTOM".into(),
+            file_options: FileOptions::default(),
             active_var: None,
         }
     }
 
+    /// Set the `bundle_comments` field written to METADATA.
+    ///
+    /// The comment may not contain line breaks, since they delimit METADATA fields; this returns
+    /// [`BundleError::InvalidMetadata`] if it does.
+    pub fn set_comments(&mut self, comments: &str) -> IoResult<()> {
+        self.comments = metadata_value("bundle_comments", comments)?;
+        Ok(())
+    }
+
+    /// Set the `bundle_target_type` field written to METADATA, which is `CUSTOM` by default.
+    ///
+    /// Like [`set_comments`](Writer::set_comments), the value may not contain line breaks.
+    pub fn set_target_type(&mut self, target_type: &str) -> IoResult<()> {
+        self.target_type = metadata_value("bundle_target_type", target_type)?;
+        Ok(())
+    }
+
     /// Begin writing a variable.
     ///
     /// Subsequent writes will append to the most recently-started variable.
-    /// Parameters are the same as [`write::Writer::new`](crate::write::Writer::new).
+    /// Parameters are the same as [`write::Writer::new`](crate::write::Writer::new), except that
+    /// empty names are rejected with [`BundleError::InvalidEntryName`].
     pub fn start_var(&mut self, ty: VariableType, name: &str, archived: bool) -> IoResult<()> {
         // Finish off the previous var, if any
-        self.close_var()?;
+        self.finish_var()?;
         // Make the new one active
-        self.active_var = Some((
-            VarWriter::new(Cursor::new(Vec::new()), ty, name, archived)?,
-            format!("{}.{}", name, ty.file_extension()),
-        ));
+        let entry_name = entry_name(ty, name)?;
+        self.active_var = Some(ActiveVar {
+            w: VarWriter::new(Cursor::new(Vec::new()), ty, name, archived)?,
+            name: entry_name,
+        });
         Ok(())
     }
 
-    fn update_crc(&mut self, data: &[u8]) {
-        self.crc_sum = self.crc_sum.wrapping_add(crc32fast::hash(data));
+    /// Set the compression method used for entries added to the bundle from now on.
+    ///
+    /// By default entries are compressed with the default method for the enabled features of
+    /// the `zip` crate: `Deflated` if any `deflate` feature is
+    /// enabled, otherwise [`Stored`](CompressionMethod::Stored). The bundle checksum is computed
+    /// over uncompressed data so it does not depend on the compression method.
+    pub fn set_compression(&mut self, method: CompressionMethod) {
+        self.file_options = self.file_options.compression_method(method);
+    }
+
+    /// Add a complete, already-encoded variable file to the bundle.
+    ///
+    /// `bytes` are stored as-is under `filename`, which must be a legal variable name followed by
+    /// the extension for a variable type (such as `PRGM.8xp`); otherwise this returns
+    /// [`BundleError::InvalidEntryName`]. Any active variable is finished first, and subsequent
+    /// writes will fail until [`start_var`](Writer::start_var) is called again.
+    pub fn add_variable_file(&mut self, filename: &str, bytes: &[u8]) -> IoResult<()> {
+        check_entry_name(filename)?;
+        self.finish_var()?;
+        self.write_entry(filename, bytes)
+    }
+
+    /// Return the sum of the CRC32s of all variables written so far.
+    ///
+    /// Only finished variables are included: the one currently being written is added when
+    /// it is finished with [`finish_var`](Writer::finish_var), the next variable is started or
+    /// the bundle is closed. The METADATA entry written by
+    /// [`close`](Writer::close) also counts towards the checksum recorded in the bundle, so
+    /// the final value will differ from this.
+    pub fn crc_sum(&self) -> u32 {
+        self.crc_sum
     }
 
-    fn close_var(&mut self) -> IoResult<()> {
+    /// Return the name and CRC32 of each variable entry written so far, in order.
+    ///
+    /// These are the CRCs that the zip records for each entry and that are summed into the
+    /// bundle checksum, which helps find the variable responsible when checksums computed by
+    /// different tools disagree. Like [`crc_sum`](Writer::crc_sum), this does not include the
+    /// variable currently being written.
+    pub fn entries(&self) -> &[(String, u32)] {
+        &self.entries
+    }
+
+    /// Return the number of variables that have been finished so far.
+    ///
+    /// Like [`crc_sum`](Writer::crc_sum), this does not include the variable currently being
+    /// written.
+    pub fn var_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return the total size of the variable files that have been finished so far, before
+    /// compression.
+    ///
+    /// Like [`crc_sum`](Writer::crc_sum), this does not include the variable currently being
+    /// written or the METADATA and _CHECKSUM entries.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Finish writing the active variable, adding it to the bundle.
+    ///
+    /// This happens automatically when another variable is started or the bundle is closed, but
+    /// calling it explicitly makes the variable's size and CRC available from
+    /// [`bytes_written`](Writer::bytes_written) and [`crc_sum`](Writer::crc_sum).
+    /// Subsequent writes will fail until [`start_var`](Writer::start_var) is called again. Does
+    /// nothing if no variable is active.
+    pub fn finish_var(&mut self) -> IoResult<()> {
         // Clear the active var and do nothing if there isn't one
-        let (w, name) = match self.active_var.take() {
+        let ActiveVar { w, name } = match self.active_var.take() {
             Some(x) => x,
             None => return Ok(()),
         };
         // Finalize the var file; we needed to buffer it since we can't seek in the zip
         let buf = w.close()?.into_inner();
-        // and we need the data to get its CRC (even though the zip writer also computes this; it's
+        self.write_entry(&name, &buf)
+    }
+
+    /// Write a variable file to a new entry in the zip.
+    fn write_entry(&mut self, name: &str, data: &[u8]) -> IoResult<()> {
+        // We need the data to get its CRC (even though the zip writer also computes this; it's
         // hard to get back out of the zip writer)
-        self.update_crc(&buf);
+        let crc = crc32fast::hash(data);
+        self.crc_sum = self.crc_sum.wrapping_add(crc);
+        self.entries.push((name.into(), crc));
+        self.bytes_written += data.len() as u64;
 
-        // Flush buffered data out to a new file within the zip
-        self.zip.start_file(name, FileOptions::default())?;
-        self.zip.write_all(&buf)
+        self.zip.start_file(name, self.file_options)?;
+        self.zip.write_all(data)
     }
 
     /// Close the archive, returning the underlying writer.
     ///
     /// This must be called in order to make the bundle valid.
-    pub fn close(mut self) -> IoResult<W> {
-        self.close_var()?;
+    pub fn close(self) -> IoResult<W> {
+        let mut zip = self.close_into_zip()?;
+        match zip.finish() {
+            Err(ZipError::Io(e)) => Err(e),
+            Err(o) => unreachable!("zip.finish() can only return IO errors, but got {:?}", o),
+            Ok(w) => Ok(w),
+        }
+    }
+
+    /// Write the bundle's metadata entries, returning the zip writer without finishing it.
+    ///
+    /// This is like [`close`](Writer::close), but allows the caller to finish the archive itself,
+    /// for example when the writer was created with [`from_zip`](Writer::from_zip). Any entries
+    /// added to the zip afterwards will follow _CHECKSUM, which TI-Connect may not accept.
+    pub fn close_into_zip(mut self) -> IoResult<ZipWriter<W>> {
+        // Variables must all precede the metadata entries
+        self.finish_var()?;
+        self.write_metadata()?;
+        // The checksum covers METADATA, so it must be written last
+        self.write_checksum()?;
+        Ok(self.zip)
+    }
 
-        self.zip.start_file("METADATA", FileOptions::default())?;
-        let metadata_contents =
-            format!(
-                "bundle_identifier:TI Bundle\n\
+    /// Write the METADATA entry, adding it to the bundle checksum.
+    fn write_metadata(&mut self) -> IoResult<()> {
+        self.zip.start_file(METADATA, self.file_options)?;
+        let metadata_contents = format!(
+            "bundle_identifier:TI Bundle\n\
              bundle_format_version:1\n\
              bundle_target_device:{}\n\
-             bundle_target_type:CUSTOM\n\
-             bundle_comments:Generated by tifiles-rs::bundle::Writer\n",
-                self.kind.metadata_device_name()
-            );
-        self.update_crc(metadata_contents.as_bytes());
-        self.zip.write_all(metadata_contents.as_bytes())?;
+             bundle_target_type:{}\n\
+             bundle_comments:{}\n",
+            self.kind.metadata_device_name(),
+            self.target_type,
+            self.comments,
+        );
+        self.crc_sum = self
+            .crc_sum
+            .wrapping_add(crc32fast::hash(metadata_contents.as_bytes()));
+        self.zip.write_all(metadata_contents.as_bytes())
+    }
 
-        self.zip.start_file("_CHECKSUM", FileOptions::default())?;
-        write!(self.zip, "{:x}", self.crc_sum)?;
+    /// Write the _CHECKSUM entry, which must be the last in the bundle.
+    fn write_checksum(&mut self) -> IoResult<()> {
+        self.zip.start_file(CHECKSUM, self.file_options)?;
+        write!(self.zip, "{:x}", self.crc_sum)
+    }
+}
 
-        match self.zip.finish() {
-            Err(ZipError::Io(e)) => Err(e),
-            Err(o) => unreachable!("zip.finish() can only return IO errors, but got {:?}", o),
-            Ok(w) => Ok(w),
+/// Return the name of the zip entry for a variable, such as `PRGM.8xp`.
+///
+/// The entry is named for the variable as it will be written, so long names are truncated in
+/// the same way as [`write::Writer::new`](crate::write::Writer::new) and it returns the same
+/// errors if the name or type can't be written. Empty names are rejected with
+/// [`BundleError::InvalidEntryName`].
+fn entry_name(ty: VariableType, name: &str) -> IoResult<String> {
+    let encoded = crate::write::encode_name(name).map_err(std::io::Error::other)?;
+    let extension = ty
+        .file_extension()
+        .ok_or(WriteError::UnsupportedType(ty))
+        .map_err(std::io::Error::other)?;
+    let name: String = encoded
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| if b == 0x5b { '\u{03b8}' } else { b as char })
+        .collect();
+    let entry_name = format!("{}.{}", name, extension);
+    check_entry_name(&entry_name)?;
+    Ok(entry_name)
+}
+
+/// Check that a zip entry name is a legal variable name of 1 to 8 characters followed by a
+/// variable file extension, as [`entry_name`] produces.
+///
+/// Path separators are rejected explicitly, so entries can't refer to other directories when
+/// extracted even if the name rules are relaxed.
+fn check_entry_name(filename: &str) -> IoResult<()> {
+    let legal = !filename.contains(['/', '\\'])
+        && filename.rsplit_once('.').is_some_and(|(name, ext)| {
+            (1..=8).contains(&name.chars().count())
+                && crate::write::encode_name(name).is_ok()
+                && VariableType::from_extension(ext).is_some()
+        });
+    if !legal {
+        return Err(BundleError::InvalidEntryName(filename.into()).into());
+    }
+    Ok(())
+}
+
+/// Collects the parameters for a bundle [`Writer`].
+///
+/// This is an alternative to setting each parameter on a writer after creating it, which checks
+/// all of them at once:
+///
+/// ```
+/// # use tifiles::bundle::{Builder, Kind};
+/// let writer = Builder::new(Kind::B84)
+///     .comments("Level pack")
+///     .build(std::io::Cursor::new(Vec::new()))
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Builder {
+    kind: Kind,
+    comments: Option<String>,
+    target_type: Option<String>,
+    compression: Option<CompressionMethod>,
+}
+
+impl Builder {
+    /// Begin building a writer for a bundle of the given kind.
+    ///
+    /// Other parameters default to the same values as for [`Writer::new`].
+    pub fn new(kind: Kind) -> Self {
+        Builder {
+            kind,
+            comments: None,
+            target_type: None,
+            compression: None,
         }
     }
+
+    /// Set the bundle kind.
+    pub fn kind(mut self, kind: Kind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Set the `bundle_comments` metadata field, as for [`Writer::set_comments`].
+    pub fn comments(mut self, comments: &str) -> Self {
+        self.comments = Some(comments.into());
+        self
+    }
+
+    /// Set the `bundle_target_type` metadata field, as for [`Writer::set_target_type`].
+    pub fn target_type(mut self, target_type: &str) -> Self {
+        self.target_type = Some(target_type.into());
+        self
+    }
+
+    /// Set the compression method for all entries, as for [`Writer::set_compression`].
+    pub fn compression(mut self, method: CompressionMethod) -> Self {
+        self.compression = Some(method);
+        self
+    }
+
+    /// Create a writer for a bundle written to `output`.
+    ///
+    /// Returns [`BundleError::InvalidMetadata`] if the comments or target type contain line
+    /// breaks, without writing anything to the output.
+    pub fn build<W: Write + Seek>(&self, output: W) -> IoResult<Writer<W>> {
+        let comments = self
+            .comments
+            .as_deref()
+            .map(|c| metadata_value("bundle_comments", c))
+            .transpose()?;
+        let target_type = self
+            .target_type
+            .as_deref()
+            .map(|t| metadata_value("bundle_target_type", t))
+            .transpose()?;
+
+        let mut writer = Writer::new(self.kind, output);
+        if let Some(comments) = comments {
+            writer.comments = comments;
+        }
+        if let Some(target_type) = target_type {
+            writer.target_type = target_type;
+        }
+        if let Some(method) = self.compression {
+            writer.set_compression(method);
+        }
+        Ok(writer)
+    }
+}
+
+/// Check that a METADATA field value can be written without corrupting the format.
+fn metadata_value(field: &'static str, value: &str) -> Result<String, BundleError> {
+    if value.contains(['\r', '\n']) {
+        return Err(BundleError::InvalidMetadata(field));
+    }
+    Ok(value.into())
 }
 
 impl<W> Write for Writer<W>
@@ -184,23 +554,572 @@ where
 {
     fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
         match self.active_var {
-            None => {
-                panic!("start_var must be called on a bundle writer before data can be written")
-            }
-            Some((ref mut v, _)) => v.write(buf),
+            None => Err(BundleError::NoActiveVar.into()),
+            Some(ref mut var) => var.w.write(buf).map_err(|e| {
+                // Identify the variable that's too large, since there may be many in a bundle
+                match WriteError::from_io(&e) {
+                    Some(&WriteError::TooLarge(size)) => BundleError::TooLarge {
+                        name: var.name.clone(),
+                        size,
+                    }
+                    .into(),
+                    _ => e,
+                }
+            }),
         }
     }
 
     fn flush(&mut self) -> IoResult<()> {
         match self.active_var {
-            None => {
-                panic!("start_var must be called on a bundle writer before data can be flushed")
+            None => Err(BundleError::NoActiveVar.into()),
+            Some(ref mut var) => var.w.flush(),
+        }
+    }
+}
+
+/// Fields parsed from a bundle's METADATA entry.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Metadata {
+    /// `bundle_identifier`, normally `TI Bundle`.
+    pub identifier: String,
+    /// `bundle_format_version`, normally 1.
+    pub format_version: u32,
+    /// `bundle_target_device`, such as `84CE`.
+    pub target_device: String,
+    /// `bundle_target_type`, normally `CUSTOM`.
+    pub target_type: String,
+    /// `bundle_comments`, which may be empty.
+    pub comments: String,
+}
+
+impl Metadata {
+    fn parse(s: &str) -> Result<Self, BundleError> {
+        let mut identifier = None;
+        let mut format_version = None;
+        let mut target_device = None;
+        let mut target_type = None;
+        let mut comments = None;
+
+        for line in s.lines() {
+            let (name, value) = match line.split_once(':') {
+                Some(x) => x,
+                None => continue,
+            };
+            let value = value.trim_end_matches('\r');
+            match name {
+                "bundle_identifier" => identifier = Some(value.to_owned()),
+                "bundle_format_version" => {
+                    format_version = Some(
+                        value
+                            .parse()
+                            .map_err(|_| BundleError::InvalidMetadata("bundle_format_version"))?,
+                    )
+                }
+                "bundle_target_device" => target_device = Some(value.to_owned()),
+                "bundle_target_type" => target_type = Some(value.to_owned()),
+                "bundle_comments" => comments = Some(value.to_owned()),
+                // Unknown fields are ignored
+                _ => {}
             }
-            Some((ref mut v, _)) => v.flush(),
         }
+
+        Ok(Metadata {
+            identifier: identifier.ok_or(BundleError::InvalidMetadata("bundle_identifier"))?,
+            format_version: format_version
+                .ok_or(BundleError::InvalidMetadata("bundle_format_version"))?,
+            target_device: target_device
+                .ok_or(BundleError::InvalidMetadata("bundle_target_device"))?,
+            target_type: target_type.unwrap_or_default(),
+            comments: comments.unwrap_or_default(),
+        })
     }
 }
 
+/// Reads bundle files.
+///
+/// The bundle's metadata is parsed when the reader is constructed, and the contained variables
+/// can then be read via [`variables`](Reader::variables). The bundle checksum is not verified
+/// automatically; call [`validate_checksum`](Reader::validate_checksum) to check it.
+pub struct Reader<R>
+where
+    R: Read + Seek,
+{
+    kind: Kind,
+    metadata: Metadata,
+    zip: ZipArchive<R>,
+}
+
+impl<R> Reader<R>
+where
+    R: Read + Seek,
+{
+    /// Open a bundle for reading.
+    ///
+    /// Returns [`BundleError::MissingEntry`] if there is no METADATA entry,
+    /// [`BundleError::InvalidMetadata`] if it cannot be parsed, or [`BundleError::UnknownDevice`]
+    /// if the target device doesn't correspond to a known [`Kind`].
+    pub fn new(reader: R) -> IoResult<Self> {
+        let mut zip = ZipArchive::new(reader)?;
+
+        let mut metadata_contents = String::new();
+        match zip.by_name(METADATA) {
+            Ok(mut f) => f.read_to_string(&mut metadata_contents)?,
+            Err(ZipError::FileNotFound) => {
+                return Err(BundleError::MissingEntry(METADATA).into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let metadata = Metadata::parse(&metadata_contents)?;
+        let kind = match Kind::from_device_name(&metadata.target_device) {
+            Some(k) => k,
+            None => return Err(BundleError::UnknownDevice(metadata.target_device).into()),
+        };
+
+        Ok(Reader {
+            kind,
+            metadata,
+            zip,
+        })
+    }
+
+    /// Get the bundle kind, as determined by its target device.
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// Get the bundle's parsed metadata.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Verify the bundle's _CHECKSUM entry.
+    ///
+    /// The checksum is compared against the sum of the CRC32s of every other entry in the
+    /// bundle (including METADATA), as recorded in the zip archive. Returns
+    /// [`BundleError::ChecksumMismatch`] if they disagree.
+    pub fn validate_checksum(&mut self) -> IoResult<()> {
+        let mut computed = 0u32;
+        for i in 0..self.zip.len() {
+            let file = self.zip.by_index_raw(i)?;
+            if file.name() != CHECKSUM {
+                computed = computed.wrapping_add(file.crc32());
+            }
+        }
+
+        let mut checksum_contents = String::new();
+        match self.zip.by_name(CHECKSUM) {
+            Ok(mut f) => f.read_to_string(&mut checksum_contents)?,
+            Err(ZipError::FileNotFound) => {
+                return Err(BundleError::MissingEntry(CHECKSUM).into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let recorded = u32::from_str_radix(checksum_contents.trim_end(), 16)
+            .map_err(|_| BundleError::InvalidMetadata(CHECKSUM))?;
+
+        if computed != recorded {
+            return Err(BundleError::ChecksumMismatch { computed, recorded }.into());
+        }
+        Ok(())
+    }
+
+    /// Iterate over the variables contained in the bundle.
+    ///
+    /// Each variable's file is read into memory before being returned, so the returned readers
+    /// are independent of the bundle.
+    pub fn variables(&mut self) -> Variables<'_, R> {
+        Variables {
+            files: self.files(),
+        }
+    }
+
+    /// Iterate over the variable files contained in the bundle, as their names and contents.
+    ///
+    /// Like [`variables`](Reader::variables) this skips the METADATA and _CHECKSUM entries, but
+    /// returns each file's contents without parsing them.
+    pub fn files(&mut self) -> Files<'_, R> {
+        Files {
+            zip: &mut self.zip,
+            index: 0,
+        }
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.zip.into_inner()
+    }
+}
+
+/// Iterator over the variables in a bundle, returned by [`Reader::variables`].
+///
+/// The METADATA and _CHECKSUM entries are skipped.
+pub struct Variables<'a, R>
+where
+    R: Read + Seek,
+{
+    files: Files<'a, R>,
+}
+
+impl<'a, R> Iterator for Variables<'a, R>
+where
+    R: Read + Seek,
+{
+    type Item = IoResult<VarReader<Cursor<Vec<u8>>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, buf) = match self.files.next()? {
+            Ok(file) => file,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(VarReader::new(Cursor::new(buf)))
+    }
+}
+
+/// Iterator over the variable files in a bundle, returned by [`Reader::files`].
+///
+/// Each item is the name of a file and its contents. The METADATA and _CHECKSUM entries are
+/// skipped.
+pub struct Files<'a, R>
+where
+    R: Read + Seek,
+{
+    zip: &'a mut ZipArchive<R>,
+    index: usize,
+}
+
+impl<'a, R> Iterator for Files<'a, R>
+where
+    R: Read + Seek,
+{
+    type Item = IoResult<(String, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.zip.len() {
+            // Advance first, so an entry that can't be read is only returned once
+            self.index += 1;
+            if let Some((name, data)) = read_entry(self.zip, self.index - 1) {
+                return Some(data.map(|data| (name, data)));
+            }
+        }
+        None
+    }
+}
+
+/// Read the zip entry at `index` as a variable file, returning its name and contents.
+///
+/// Returns `None` for the METADATA and _CHECKSUM entries. If the entry can't be opened at all,
+/// its name is unknown so it is named by its index instead, like `entry 1`.
+fn read_entry<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    index: usize,
+) -> Option<(String, IoResult<Vec<u8>>)> {
+    let mut file = match zip.by_index(index) {
+        Ok(f) => f,
+        Err(e) => return Some((format!("entry {}", index), Err(e.into()))),
+    };
+    if [METADATA, CHECKSUM].contains(&file.name()) {
+        return None;
+    }
+
+    let mut buf = Vec::with_capacity(file.size() as usize);
+    let data = file.read_to_end(&mut buf).map(|_| buf);
+    Some((file.name().to_owned(), data))
+}
+
+/// Extract every variable in a bundle to a separate file in `out_dir`.
+///
+/// Each variable is written to a file with the same name as its bundle entry (such as
+/// `PRGM.8xp`), replacing any existing file. If `verify` is true the bundle checksum is checked
+/// first, as with [`Reader::validate_checksum`], and nothing is extracted if it is invalid.
+///
+/// Errors opening or verifying the bundle are returned immediately. Otherwise this returns the
+/// name of every entry along with the path it was written to, or the error that prevented it from
+/// being extracted, so one bad entry doesn't prevent the others from being extracted. Entry names
+/// that aren't plain file names (because they contain a directory) can't be extracted and return
+/// errors of kind [`InvalidData`](std::io::ErrorKind::InvalidData). Entries that are so corrupt
+/// they can't be opened are named by their index in the zip, like `entry 1`.
+///
+/// ```
+/// # use tifiles::bundle::extract_all;
+/// # fn doit() -> std::io::Result<()> {
+/// let bundle = std::fs::File::open("programs.b84")?;
+/// for (name, result) in extract_all(bundle, "programs", true)? {
+///     match result {
+///         Ok(path) => println!("Extracted {} to {}", name, path.display()),
+///         Err(e) => eprintln!("Failed to extract {}: {}", name, e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn extract_all<R: Read + Seek>(
+    input: R,
+    out_dir: impl AsRef<Path>,
+    verify: bool,
+) -> IoResult<Vec<(String, IoResult<PathBuf>)>> {
+    let mut reader = Reader::new(input)?;
+    if verify {
+        reader.validate_checksum()?;
+    }
+
+    let mut results = Vec::new();
+    for index in 0..reader.zip.len() {
+        if let Some((name, data)) = read_entry(&mut reader.zip, index) {
+            let result = data.and_then(|data| extract_file(out_dir.as_ref(), &name, &data));
+            results.push((name, result));
+        }
+    }
+    Ok(results)
+}
+
+/// Write a single file for [`extract_all`], returning its path.
+fn extract_file(out_dir: &Path, name: &str, data: &[u8]) -> IoResult<PathBuf> {
+    let is_plain = Path::new(name).file_name().is_some_and(|n| n == name);
+    if !is_plain {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("bundle entry {:?} is not a plain file name", name),
+        ));
+    }
+    let path = out_dir.join(name);
+    std::fs::write(&path, data)?;
+    Ok(path)
+}
+
+#[test]
+fn kinds_round_trip() {
+    for kind in [Kind::B83, Kind::B84] {
+        assert_eq!(Kind::from_extension(kind.file_extension()), Some(kind));
+        assert_eq!(
+            Kind::from_device_name(kind.metadata_device_name()),
+            Some(kind)
+        );
+    }
+    assert_eq!(Kind::from_extension("B84"), Some(Kind::B84));
+    assert_eq!(Kind::from_device_name("83ce"), Some(Kind::B83));
+    assert_eq!(Kind::from_extension("8xp"), None);
+    assert_eq!(Kind::from_device_name("TI84"), None);
+}
+
+/// TI-Connect rejects bundles with entries in any other order.
+#[test]
+fn entries_are_ordered() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    w.add_variable_file("B.8xv", b"not really a variable")
+        .unwrap();
+    w.start_var(VariableType::Program, "C", false).unwrap();
+    // Left active until closed
+    w.write_all(b"\xc9").unwrap();
+    let data = w.close().unwrap().into_inner();
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+    let names = (0..zip.len())
+        .map(|i| zip.by_index(i).unwrap().name().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["A.8xv", "B.8xv", "C.8xp", "METADATA", "_CHECKSUM"]);
+}
+
+#[test]
+fn existing_zips_are_adopted() {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.set_comment("shared archive");
+    let mut w = Writer::from_zip(Kind::B83, zip);
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    w.write_all(b"data").unwrap();
+
+    // Returned open, so more entries can follow
+    let mut zip = w.close_into_zip().unwrap();
+    zip.start_file("extra.txt", FileOptions::default()).unwrap();
+    zip.write_all(b"not part of the bundle").unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+    assert_eq!(zip.comment(), b"shared archive");
+    let names = (0..zip.len())
+        .map(|i| zip.by_index(i).unwrap().name().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["A.8xv", "METADATA", "_CHECKSUM", "extra.txt"]);
+}
+
+#[test]
+fn builder_configures_writers() {
+    let w = Builder::new(Kind::B83)
+        .kind(Kind::B84)
+        .comments("Built")
+        .target_type("OTHER")
+        .compression(CompressionMethod::Stored)
+        .build(Cursor::new(Vec::new()))
+        .unwrap();
+    let data = w.close().unwrap().into_inner();
+    let mut zip = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+    let mut metadata = String::new();
+    zip.by_name(METADATA)
+        .unwrap()
+        .read_to_string(&mut metadata)
+        .unwrap();
+    assert!(metadata.contains("bundle_target_device:84CE\n"));
+    assert!(metadata.contains("bundle_target_type:OTHER\n"));
+    assert!(metadata.contains("bundle_comments:Built\n"));
+
+    let mut output = Cursor::new(Vec::new());
+    let err = Builder::new(Kind::B84)
+        .comments("two\nlines")
+        .build(&mut output)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.get_ref().and_then(|e| e.downcast_ref()),
+        Some(BundleError::InvalidMetadata("bundle_comments"))
+    ));
+    assert!(output.get_ref().is_empty());
+}
+
+#[test]
+fn entry_names_are_validated() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    for name in [
+        "../ESCAPE.8xv",
+        "DIR/A.8xv",
+        "DIR\\A.8xv",
+        "lower.8xv",
+        "TOOLONGNAME.8xv",
+        ".8xv",
+        "A.txt",
+        "A",
+    ] {
+        let e = w.add_variable_file(name, b"").unwrap_err();
+        assert!(
+            matches!(
+                e.get_ref().and_then(|e| e.downcast_ref()),
+                Some(BundleError::InvalidEntryName(n)) if n == name
+            ),
+            "{:?} should be rejected",
+            name
+        );
+    }
+    w.add_variable_file("\u{3b8}1.8XP", b"").unwrap();
+
+    let e = w
+        .start_var(VariableType::Program, "../A", false)
+        .unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&e),
+        Some(WriteError::InvalidName)
+    ));
+    assert_eq!(w.var_count(), 1);
+
+    let e = w.start_var(VariableType::Program, "", false).unwrap_err();
+    assert!(matches!(
+        e.get_ref().and_then(|e| e.downcast_ref()),
+        Some(BundleError::InvalidEntryName(n)) if n == ".8xp"
+    ));
+    assert_eq!(w.var_count(), 1);
+}
+
+#[test]
+fn long_variable_names_are_truncated() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::Program, "LONGERNAME", false)
+        .unwrap();
+    w.start_var(
+        VariableType::AppVar,
+        "\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}",
+        false,
+    )
+    .unwrap();
+    w.finish_var().unwrap();
+    let names: Vec<_> = w.entries().iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "LONGERNA.8xp",
+            "\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}.8xv"
+        ]
+    );
+
+    let mut bundle = w.close().unwrap();
+    bundle.set_position(0);
+    let mut reader = Reader::new(bundle).unwrap();
+    let var_names: Vec<_> = reader
+        .variables()
+        .map(|var| var.unwrap().name_str())
+        .collect();
+    assert_eq!(
+        var_names,
+        [
+            "LONGERNA",
+            "\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}\u{3b8}"
+        ]
+    );
+}
+
+#[test]
+fn extracts_files() {
+    let out_dir = std::env::temp_dir().join(format!("tifiles-extract-{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::Program, "PRGM", false).unwrap();
+    let data = w.close().unwrap().into_inner();
+    let results = extract_all(Cursor::new(&data), &out_dir, true).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].1.is_ok());
+
+    // Writers reject entry names that aren't plain file names, but other software may not
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("../ESCAPE.8xv", FileOptions::default())
+        .unwrap();
+    zip.write_all(b"not really a variable").unwrap();
+    let mut w = Writer::from_zip(Kind::B84, zip);
+    w.start_var(VariableType::Program, "PRGM", false).unwrap();
+    w.write_all(b"\xc9").unwrap();
+    w.add_variable_file("DATA.8xv", b"also not a variable")
+        .unwrap();
+    let data = w.close().unwrap().into_inner();
+
+    let results = extract_all(Cursor::new(&data), &out_dir, false).unwrap();
+    let names = results.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["../ESCAPE.8xv", "PRGM.8xp", "DATA.8xv"]);
+
+    let prgm = results[1].1.as_ref().unwrap();
+    assert_eq!(*prgm, out_dir.join("PRGM.8xp"));
+    assert_eq!(
+        VarReader::new(std::fs::File::open(prgm).unwrap())
+            .unwrap()
+            .name_str(),
+        "PRGM"
+    );
+    let e = results[0].1.as_ref().unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+    assert!(!out_dir.join("../ESCAPE.8xv").exists());
+    assert_eq!(
+        std::fs::read(out_dir.join("DATA.8xv")).unwrap(),
+        b"also not a variable"
+    );
+    std::fs::remove_dir_all(&out_dir).unwrap();
+
+    // Entries already in an adopted zip aren't included in the bundle checksum
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("EXTRA.8xv", FileOptions::default()).unwrap();
+    zip.write_all(b"extra").unwrap();
+    let data = Writer::from_zip(Kind::B84, zip)
+        .close()
+        .unwrap()
+        .into_inner();
+    let e = extract_all(Cursor::new(&data), &out_dir, true).unwrap_err();
+    assert!(matches!(
+        e.get_ref().and_then(|e| e.downcast_ref()),
+        Some(BundleError::ChecksumMismatch { .. })
+    ));
+    assert!(!out_dir.exists());
+
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let results = extract_all(Cursor::new(&data), &out_dir, false).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(std::fs::read(out_dir.join("EXTRA.8xv")).unwrap(), b"extra");
+    std::fs::remove_dir_all(&out_dir).unwrap();
+}
+
 #[test]
 fn crc_matches_metafile() {
     let mut w = Writer::new(Kind::B83, Cursor::new(Vec::new()));
@@ -229,3 +1148,255 @@ fn crc_matches_metafile() {
         "Actual zip CRCs did not match CHECKSUM file"
     );
 }
+
+#[test]
+fn running_crc_tracks_finished_vars() {
+    let mut w = Writer::new(Kind::B83, Cursor::new(Vec::new()));
+    assert_eq!((w.crc_sum(), w.var_count()), (0, 0));
+
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    write!(w, "var one data").unwrap();
+    assert_eq!((w.crc_sum(), w.var_count()), (0, 0));
+    w.start_var(VariableType::AppVar, "B", false).unwrap();
+    assert_eq!(w.var_count(), 1);
+    let running = w.crc_sum();
+    let data = w.close().unwrap().into_inner();
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+    assert_eq!(running, zip.by_name("A.8xv").unwrap().crc32());
+}
+
+#[test]
+fn custom_metadata_is_written() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    assert!(w.set_comments("two\nlines").is_err());
+    assert!(w.set_target_type("CR\r").is_err());
+    w.set_comments("Made with my tool").unwrap();
+    w.set_target_type("OTHER").unwrap();
+    let data = w.close().unwrap().into_inner();
+
+    let r = Reader::new(Cursor::new(data)).unwrap();
+    assert_eq!(r.metadata().comments, "Made with my tool");
+    assert_eq!(r.metadata().target_type, "OTHER");
+}
+
+#[test]
+fn prebuilt_files_are_included() {
+    let mut var =
+        VarWriter::new(Cursor::new(Vec::new()), VariableType::AppVar, "RAW", false).unwrap();
+    var.write_all(b"prebuilt").unwrap();
+    let var = var.close().unwrap().into_inner();
+
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    w.add_variable_file("RAW.8xv", &var).unwrap();
+    assert_eq!(w.var_count(), 2);
+    let data = w.close().unwrap().into_inner();
+
+    let mut r = Reader::new(Cursor::new(data)).unwrap();
+    r.validate_checksum()
+        .expect("checksum should include prebuilt file");
+    let vars = r.variables().collect::<IoResult<Vec<_>>>().unwrap();
+    assert_eq!(vars[1].name(), b"RAW\0\0\0\0\0");
+}
+
+#[test]
+fn compression_is_configurable() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.set_compression(CompressionMethod::Stored);
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    w.write_all(&[0; 100]).unwrap();
+    let data = w.close().unwrap().into_inner();
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(data.clone())).unwrap();
+    for i in 0..zip.len() {
+        let file = zip.by_index(i).unwrap();
+        assert_eq!(file.compression(), CompressionMethod::Stored);
+    }
+    Reader::new(Cursor::new(data))
+        .unwrap()
+        .validate_checksum()
+        .unwrap();
+
+    // Methods that aren't compiled in are reported as errors
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.set_compression(CompressionMethod::BZIP2);
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    assert!(w.close().is_err());
+}
+
+#[test]
+fn writing_without_var_is_an_error() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    let err = w.write_all(b"data").unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<BundleError>(),
+        Some(BundleError::NoActiveVar)
+    ));
+    assert!(w.flush().is_err());
+
+    // The writer is still usable afterwards
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    w.write_all(b"data").unwrap();
+    w.close().unwrap();
+}
+
+#[test]
+fn finishing_vars_commits_them() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    w.write_all(b"data").unwrap();
+    assert_eq!(w.var_count(), 0);
+    w.finish_var().unwrap();
+    assert_eq!(w.var_count(), 1);
+    assert_eq!(w.bytes_written(), 80);
+    assert_ne!(w.crc_sum(), 0);
+
+    // Nothing is active now, so writing fails and finishing again does nothing
+    assert!(w.write_all(b"more").is_err());
+    w.finish_var().unwrap();
+    assert_eq!(w.var_count(), 1);
+
+    let bundle = w.close().unwrap().into_inner();
+    let mut zip = zip::ZipArchive::new(Cursor::new(bundle)).unwrap();
+    assert_eq!(zip.by_name("A.8xv").unwrap().size(), 80);
+}
+
+#[test]
+fn oversized_vars_are_rejected() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::AppVar, "BIG", false).unwrap();
+    // The length prefix counts towards the limit
+    let mut data = vec![0u8; crate::MAX_DATA as usize - 2];
+    w.write_all(&data).unwrap();
+    let err = w.write_all(&[0]).unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<BundleError>(),
+        Some(BundleError::TooLarge { name, size: 65519 }) if name == "BIG.8xv"
+    ));
+
+    w.start_var(VariableType::AppVar, "SMALL", false).unwrap();
+    // Header and checksum around a data section of the maximum size
+    assert_eq!(w.bytes_written(), 55 + u16::MAX as u64 + 2);
+    data.truncate(10);
+    w.write_all(&data).unwrap();
+    w.close().unwrap();
+}
+
+#[test]
+fn crc_sum_matches_zip() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::Real, "A", false).unwrap();
+    w.write_all(b"\x00\x80").unwrap();
+    w.write_all(&[0x10, 0, 0, 0, 0, 0, 0]).unwrap();
+    w.start_var(VariableType::Program, "PRGM", false).unwrap();
+    for _ in 0..100 {
+        w.write_all(b"\xde\x2aHELLO\x2a\x3f").unwrap();
+    }
+    w.add_variable_file("RAW.8xv", b"not really a variable")
+        .unwrap();
+    let running = w.crc_sum();
+    let recorded = w.entries().to_vec();
+    let data = w.close().unwrap().into_inner();
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+    let entries = ["A.8xn", "PRGM.8xp", "RAW.8xv"]
+        .iter()
+        .map(|name| (name.to_string(), zip.by_name(name).unwrap().crc32()))
+        .collect::<Vec<_>>();
+    assert_eq!(recorded, entries);
+    assert_eq!(
+        running,
+        entries.iter().map(|e| e.1).fold(0u32, u32::wrapping_add)
+    );
+}
+
+#[test]
+fn reader_round_trips_writer() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.start_var(VariableType::ProtectedProgram, "NOP", false)
+        .unwrap();
+    w.write_all(&[0xbb, 0x6d, 0xc9]).unwrap();
+    w.start_var(VariableType::AppVar, "GREETZ", true).unwrap();
+    w.write_all(b"Hello, world!").unwrap();
+    let data = w.close().unwrap();
+
+    let mut r = Reader::new(Cursor::new(data.into_inner())).unwrap();
+    assert_eq!(r.kind(), Kind::B84);
+    assert_eq!(r.metadata().identifier, "TI Bundle");
+    assert_eq!(r.metadata().format_version, 1);
+    assert_eq!(r.metadata().target_type, "CUSTOM");
+    r.validate_checksum().expect("checksum should be valid");
+
+    let vars = r.variables().collect::<IoResult<Vec<_>>>().unwrap();
+    assert_eq!(vars.len(), 2);
+    assert_eq!(vars[0].ty(), VariableType::ProtectedProgram);
+    assert_eq!(vars[0].name(), b"NOP\0\0\0\0\0");
+    assert_eq!(vars[1].ty(), VariableType::AppVar);
+    assert!(vars[1].is_archived());
+
+    let mut contents = vec![];
+    let mut greetz = vars.into_iter().nth(1).unwrap();
+    greetz.read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, b"Hello, world!");
+}
+
+#[test]
+fn unreadable_entries_are_skipped() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    for name in ["A", "B", "C"] {
+        w.start_var(VariableType::AppVar, name, false).unwrap();
+    }
+    let mut data = w.close().unwrap().into_inner();
+    // Corrupt the local header signature of the second entry so it can't be opened
+    let headers = data
+        .windows(4)
+        .enumerate()
+        .filter(|(_, w)| w == b"PK\x03\x04")
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    data[headers[1]] = b'X';
+
+    let mut reader = Reader::new(Cursor::new(data)).unwrap();
+    let files = reader.files().collect::<Vec<_>>();
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[0].as_ref().unwrap().0, "A.8xv");
+    assert!(files[1].is_err());
+    assert_eq!(files[2].as_ref().unwrap().0, "C.8xv");
+}
+
+#[test]
+fn extraction_continues_past_corrupt_entries() {
+    let mut w = Writer::new(Kind::B84, Cursor::new(Vec::new()));
+    w.set_compression(CompressionMethod::Stored);
+    for name in ["A", "B", "C", "D"] {
+        w.add_variable_file(
+            &format!("{}.8xv", name),
+            format!("data {}", name).as_bytes(),
+        )
+        .unwrap();
+    }
+    let mut data = w.close().unwrap().into_inner();
+    // B's data no longer matches its CRC, and C can't be opened at all
+    let b = data.windows(6).position(|w| w == b"data B").unwrap();
+    data[b] = b'D';
+    let headers = data
+        .windows(4)
+        .enumerate()
+        .filter(|(_, w)| w == b"PK\x03\x04")
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    data[headers[2]] = b'X';
+
+    let out_dir = std::env::temp_dir().join(format!("tifiles-corrupt-{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let results = extract_all(Cursor::new(data), &out_dir, false).unwrap();
+    let names = results.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["A.8xv", "B.8xv", "entry 2", "D.8xv"]);
+    assert!(results[1].1.is_err());
+    assert!(results[2].1.is_err());
+    assert!(!out_dir.join("B.8xv").exists());
+    assert_eq!(std::fs::read(out_dir.join("A.8xv")).unwrap(), b"data A");
+    assert_eq!(std::fs::read(out_dir.join("D.8xv")).unwrap(), b"data D");
+    std::fs::remove_dir_all(&out_dir).unwrap();
+}
@@ -0,0 +1,51 @@
+//! The checksum used in variable files.
+
+/// Computes the 16-bit checksum stored at the end of variable files.
+///
+/// The checksum is the sum of all bytes in the data section (everything following the data
+/// section length, up to the checksum itself, as described in [the file
+/// layout](crate#file-layout)), truncated to 16 bits: that is, a wrapping sum of the bytes as
+/// `u16`s. It is stored little-endian in the last two bytes of the file. This can be used to
+/// check files that are already in memory:
+///
+/// ```
+/// # use tifiles::Checksum;
+/// fn checksum_is_valid(file: &[u8]) -> bool {
+///     if file.len() < 57 {
+///         return false;
+///     }
+///     let (contents, stored) = file.split_at(file.len() - 2);
+///     let mut checksum = Checksum::new();
+///     checksum.update(&contents[55..]);
+///     checksum.finalize() == u16::from_le_bytes([stored[0], stored[1]])
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Checksum(pub(crate) u16);
+
+impl Checksum {
+    /// Create a checksum of no data.
+    pub fn new() -> Self {
+        Checksum(0)
+    }
+
+    /// Add data to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.0 = self.0.wrapping_add(b as u16);
+        }
+    }
+
+    /// Return the checksum of all data that has been added.
+    pub fn finalize(self) -> u16 {
+        self.0
+    }
+}
+
+#[test]
+fn checksum_wraps() {
+    let mut checksum = Checksum::new();
+    checksum.update(&[0xff; 257]);
+    checksum.update(&[1, 2]);
+    assert_eq!(checksum.finalize(), 2);
+}
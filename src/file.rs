@@ -0,0 +1,107 @@
+//! Whole-file reading and writing.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::io::{Read, Seek, Write};
+use crate::read::ReadError;
+use crate::{Reader, VariableType, Writer};
+
+/// The complete contents of a variable file.
+///
+/// This reads or writes an entire file at once with a [`Reader`] or [`Writer`], for when the
+/// data is wanted all together and there's no need to stream it:
+///
+/// ```
+/// # use tifiles::{VariableFile, VariableType};
+/// let file = VariableFile {
+///     ty: VariableType::AppVar,
+///     name: "SAVE".into(),
+///     archived: true,
+///     data: b"high score: 9001".to_vec(),
+/// };
+/// let mut out = tifiles::io::Cursor::new(Vec::new());
+/// file.write_to(&mut out).unwrap();
+///
+/// let read = VariableFile::read_from(&*out.into_inner()).unwrap();
+/// assert_eq!(read, file);
+/// ```
+///
+/// Only the variable itself is represented, so file comments and variable versions are not
+/// preserved: files are written with the same defaults as [`Writer::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableFile {
+    /// The type of the variable.
+    pub ty: VariableType,
+    /// The variable name, as returned by [`Reader::name_str`] and accepted by [`Writer::new`].
+    pub name: String,
+    /// Whether the variable is marked as archived.
+    pub archived: bool,
+    /// The variable data, excluding any length prefix.
+    pub data: Vec<u8>,
+}
+
+impl VariableFile {
+    /// Read a variable file from the provided input.
+    ///
+    /// Returns the same errors as [`Reader::new`], or [`ReadError::InvalidChecksum`] if the
+    /// file's checksum does not match its contents.
+    pub fn read_from<R: Read>(r: R) -> crate::io::Result<Self> {
+        let mut reader = Reader::new(r)?;
+        let data = reader.read_all_data()?;
+
+        let ty = reader.ty();
+        let name = reader.name_str();
+        let archived = reader.is_archived();
+        if let Err(e) = reader.finish()? {
+            return Err(ReadError::InvalidChecksum {
+                computed: e.computed_checksum(),
+                read: e.read_checksum(),
+            }
+            .into());
+        }
+
+        Ok(VariableFile {
+            ty,
+            name,
+            archived,
+            data,
+        })
+    }
+
+    /// Write this variable as a file to the provided output.
+    ///
+    /// Returns the same errors as [`Writer::new`] and [`Writer::close`], such as when the name
+    /// is invalid or the data is too large.
+    pub fn write_to<W: Write + Seek>(&self, w: W) -> crate::io::Result<()> {
+        let mut writer = Writer::new(w, self.ty, &self.name, self.archived)?;
+        writer.write_all(&self.data)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+#[test]
+fn round_trips_files() {
+    use crate::io::Cursor;
+
+    let file = VariableFile {
+        ty: VariableType::Program,
+        name: "\u{03b8}PRGM".into(),
+        archived: false,
+        data: b"\xde\x2a\x48\x49".to_vec(),
+    };
+    let mut out = Cursor::new(Vec::new());
+    file.write_to(&mut out).unwrap();
+    let bytes = out.into_inner();
+    assert_eq!(VariableFile::read_from(&*bytes).unwrap(), file);
+
+    // Corrupt the checksum
+    let mut bytes = bytes;
+    *bytes.last_mut().unwrap() ^= 1;
+    let err = VariableFile::read_from(&*bytes).unwrap_err();
+    assert!(matches!(
+        err.get_ref().and_then(|e| e.downcast_ref::<ReadError>()),
+        Some(ReadError::InvalidChecksum { .. })
+    ));
+}
@@ -0,0 +1,305 @@
+//! Group variables
+//!
+//! A group (8xg) is a single variable that contains several other variables, which is the way
+//! calculators themselves package variables for transfer and backup. Groups can be read with
+//! [`Reader::group_members`](crate::Reader::group_members) and written with a [`Writer`]:
+//!
+//! ```
+//! use tifiles::io::Write;
+//! use tifiles::{VariableType, group::Writer};
+//!
+//! # fn doit() -> Result<(), Box<dyn std::error::Error>> {
+//! let outf = tifiles::io::Cursor::new(Vec::new());
+//! let mut group = Writer::new(outf, "GRP", false)?;
+//!
+//! // Writes to the group append to the most recently started var
+//! group.start_var(VariableType::ProtectedProgram, "NOP", false)?;
+//! group.write_all(&[0xbb, 0x6d, 0xc9])?;
+//!
+//! group.start_var(VariableType::AppVar, "GREETZ", true)?;
+//! group.write_all(b"Hello, world!")?;
+//!
+//! // The group must be closed to be valid
+//! group.close()?;
+//! # Ok(())
+//! # }
+//! # doit().unwrap();
+//! ```
+//!
+//! The layout of group data is described in the documentation of [`GroupMember`].
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::io::{Result as IoResult, Seek, Write};
+
+use crate::read::group::{data_size, has_named_entry};
+use crate::write::{encode_name, WriteError};
+use crate::{VariableType, Writer as VarWriter, MAX_DATA};
+
+pub use crate::read::GroupMember;
+
+/// Writes group files.
+///
+/// A group contains zero or more variables, which are written using the
+/// [`Write` impl](impl std::io::Write). For each call to [`start_var`](Writer::start_var),
+/// subsequent writes will append to that variable's data. Writing before any variable has been
+/// started fails with [`WriteError::NoActiveVar`].
+///
+/// Users must call [`close`](Writer::close) when done writing all variables
+/// in order to emit a valid file.
+pub struct Writer<W>
+where
+    W: Write + Seek,
+{
+    w: VarWriter<W>,
+    committed: usize,
+    /// Buffered entry for the active var, its type, and the offset of its data (including any
+    /// length prefix) in the entry.
+    active_var: Option<(Vec<u8>, VariableType, usize)>,
+}
+
+impl<W> Writer<W>
+where
+    W: Write + Seek,
+{
+    /// Open an output for writing a group with the given name.
+    ///
+    /// Parameters are the same as [`write::Writer::new`](crate::write::Writer::new), except
+    /// the variable type is always [`Group`](VariableType::Group).
+    pub fn new(output: W, name: &str, archived: bool) -> IoResult<Self> {
+        Ok(Writer {
+            w: VarWriter::new(output, VariableType::Group, name, archived)?,
+            committed: 0,
+            active_var: None,
+        })
+    }
+
+    /// Begin writing a variable.
+    ///
+    /// Subsequent writes will append to the most recently-started variable. Names are
+    /// validated in the same way as [`write::Writer::new`](crate::write::Writer::new), except
+    /// that types other than programs, AppVars and groups have 3-byte names in a group so longer
+    /// names return [`WriteError::InvalidName`].
+    /// If `archived` is true, the variable will be archived when the group is ungrouped.
+    ///
+    /// When the variable is finished (by starting the next one or closing the group), this
+    /// returns [`WriteError::InvalidMemberSize`] if a fixed-size type like
+    /// [`Real`](VariableType::Real) has the wrong amount of data, or a list or matrix has a
+    /// different number of elements than its size says.
+    pub fn start_var(&mut self, ty: VariableType, name: &str, archived: bool) -> IoResult<()> {
+        // Finish off the previous var, if any
+        self.close_var()?;
+
+        let name = encode_name(name).map_err(crate::io::Error::other)?;
+        let has_length_prefix = ty
+            .has_length_prefix()
+            .ok_or(WriteError::UnsupportedType(ty))
+            .map_err(crate::io::Error::other)?;
+        let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        let name = if has_named_entry(ty) {
+            &name[..len]
+        } else if len <= 3 {
+            &name[..3]
+        } else {
+            return Err(crate::io::Error::other(WriteError::InvalidName));
+        };
+
+        // Entry header: type, T2, version, address and flash page, then the name
+        let mut entry = vec![ty.to_byte(), 0, 0, 0, 0, if archived { 1 } else { 0 }];
+        if has_named_entry(ty) {
+            entry.push(name.len() as u8);
+        }
+        entry.extend_from_slice(name);
+        let data_offset = entry.len();
+        if has_length_prefix {
+            // Populated when the var is closed
+            entry.extend_from_slice(&[0, 0]);
+        }
+        self.ensure_fits(entry.len())?;
+
+        self.active_var = Some((entry, ty, data_offset));
+        Ok(())
+    }
+
+    /// Return an error if adding `len` more bytes to the active var would exceed [`MAX_DATA`].
+    fn ensure_fits(&self, len: usize) -> IoResult<()> {
+        let pending = self.active_var.as_ref().map_or(0, |(buf, _, _)| buf.len());
+        // The outer group's length prefix also counts against the data size
+        let total = 2 + self.committed + pending + len;
+        if total > MAX_DATA as usize {
+            return Err(crate::io::Error::other(WriteError::TooLarge(total)));
+        }
+        Ok(())
+    }
+
+    fn close_var(&mut self) -> IoResult<()> {
+        // Clear the active var and do nothing if there isn't one
+        let (mut buf, ty, data_offset) = match self.active_var.take() {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let data = &mut buf[data_offset..];
+        if ty.has_length_prefix() == Some(true) {
+            // Length prefix excludes itself
+            let data_len = (data.len() - 2) as u16;
+            data[..2].copy_from_slice(&data_len.to_le_bytes());
+        }
+        // Readers find the end of the data from its contents, which must agree with its size
+        if data_size(ty, data).ok() != Some(data.len()) {
+            return Err(crate::io::Error::other(WriteError::InvalidMemberSize {
+                ty,
+                len: data.len(),
+            }));
+        }
+
+        self.committed += buf.len();
+        self.w.write_all(&buf)
+    }
+
+    /// Close the group, returning the underlying writer.
+    ///
+    /// This must be called in order to make the group valid.
+    pub fn close(mut self) -> IoResult<W> {
+        self.close_var()?;
+        self.w.close()
+    }
+}
+
+impl<W> Write for Writer<W>
+where
+    W: Write + Seek,
+{
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        self.ensure_fits(buf.len())?;
+        match self.active_var {
+            None => Err(crate::io::Error::other(WriteError::NoActiveVar)),
+            Some((ref mut v, _, _)) => v.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        // Data is only written to the output when a var is closed
+        Ok(())
+    }
+}
+
+#[test]
+fn group_round_trips() {
+    use crate::io::Cursor;
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), "GRP", true).unwrap();
+    w.start_var(VariableType::ProtectedProgram, "NOP", false)
+        .unwrap();
+    w.write_all(&[0xbb, 0x6d, 0xc9]).unwrap();
+    w.start_var(VariableType::Real, "X", true).unwrap();
+    w.write_all(b"\x00\x80\x10\0\0\0\0\0\0").unwrap();
+    w.start_var(VariableType::AppVar, "EMPTY", false).unwrap();
+    let data = w.close().unwrap().into_inner();
+
+    let mut r = crate::Reader::new(&*data).unwrap();
+    assert_eq!(r.ty(), VariableType::Group);
+    assert_eq!(r.name(), b"GRP\0\0\0\0\0");
+    let members = r.group_members().unwrap();
+    r.finish().unwrap().expect("checksum should be valid");
+
+    assert_eq!(members.len(), 3);
+    assert_eq!(members[0].ty(), VariableType::ProtectedProgram);
+    assert_eq!(members[0].name(), b"NOP\0\0\0\0\0");
+    assert!(!members[0].is_archived());
+    assert_eq!(members[0].data(), &[0xbb, 0x6d, 0xc9]);
+    assert_eq!(members[1].ty(), VariableType::Real);
+    assert_eq!(members[1].name(), b"X\0\0\0\0\0\0\0");
+    assert!(members[1].is_archived());
+    assert_eq!(members[1].data(), b"\x00\x80\x10\0\0\0\0\0\0");
+    assert_eq!(members[2].ty(), VariableType::AppVar);
+    assert!(members[2].data().is_empty());
+}
+
+#[test]
+fn writing_without_a_var_fails() {
+    use crate::io::Cursor;
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), "GRP", false).unwrap();
+    let e = w.write_all(b"data").unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&e),
+        Some(WriteError::NoActiveVar)
+    ));
+    // Nothing was added to the group
+    w.start_var(VariableType::AppVar, "A", false).unwrap();
+    let data = w.close().unwrap().into_inner();
+    let mut r = crate::Reader::new(&*data).unwrap();
+    assert!(r.group_members().unwrap()[0].data().is_empty());
+}
+
+#[test]
+fn short_names_are_not_truncated() {
+    use crate::io::Cursor;
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), "GRP", false).unwrap();
+    let e = w
+        .start_var(VariableType::Real, "ABCDEFG", false)
+        .unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&e),
+        Some(WriteError::InvalidName)
+    ));
+    w.start_var(VariableType::Real, "ABC", false).unwrap();
+    w.write_all(b"\x00\x80\x10\0\0\0\0\0\0").unwrap();
+    // Programs have longer names
+    w.start_var(VariableType::Program, "ABCDEFG", false)
+        .unwrap();
+    let data = w.close().unwrap().into_inner();
+
+    let mut r = crate::Reader::new(&*data).unwrap();
+    let members = r.group_members().unwrap();
+    assert_eq!(members[0].name(), b"ABC\0\0\0\0\0");
+    assert_eq!(members[1].name(), b"ABCDEFG\0");
+}
+
+#[test]
+fn member_sizes_are_checked() {
+    use crate::io::Cursor;
+
+    fn write_member(ty: VariableType, data: &[u8]) -> IoResult<Vec<u8>> {
+        let mut w = Writer::new(Cursor::new(Vec::new()), "GRP", false)?;
+        w.start_var(ty, "A", false)?;
+        w.write_all(data)?;
+        Ok(w.close()?.into_inner())
+    }
+
+    const REAL: &[u8] = b"\x00\x80\x10\0\0\0\0\0\0";
+    for (ty, data) in [
+        (VariableType::Real, &REAL[..8]),
+        (VariableType::Real, &[REAL, REAL].concat()[..]),
+        (VariableType::Complex, REAL),
+        (VariableType::List, &[b"\x02\0", REAL].concat()[..]),
+        (VariableType::ComplexList, b"\x01\0"),
+        (VariableType::Matrix, &[b"\x01\x02", REAL].concat()[..]),
+        (VariableType::Matrix, b"\x01"),
+    ] {
+        let e = write_member(ty, data).unwrap_err();
+        assert!(
+            matches!(
+                WriteError::from_io(&e),
+                Some(&WriteError::InvalidMemberSize { ty: t, len }) if t == ty && len == data.len()
+            ),
+            "{:?} with {} bytes should be rejected",
+            ty,
+            data.len()
+        );
+    }
+
+    for (ty, data) in [
+        (VariableType::Real, REAL),
+        (VariableType::List, &[b"\x01\0", REAL].concat()[..]),
+        (VariableType::Matrix, &[b"\x01\x01", REAL].concat()[..]),
+        (VariableType::Matrix, b"\0\0"),
+    ] {
+        let data = write_member(ty, data).unwrap();
+        let mut r = crate::Reader::new(&*data).unwrap();
+        assert_eq!(r.group_members().unwrap()[0].ty(), ty);
+    }
+}
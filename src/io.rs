@@ -0,0 +1,375 @@
+//! Byte stream traits used by readers and writers.
+//!
+//! With the `std` feature (enabled by default) this module simply re-exports the relevant items
+//! from [`std::io`]. Without it, minimal equivalents are provided so that variable files can be
+//! read and written in `no_std` environments that have an allocator. Users in such environments
+//! implement [`Read`], [`Write`] and [`Seek`] from this module for their own streams.
+
+#[cfg(feature = "std")]
+pub use std::io::{Cursor, Error, ErrorKind, Read, Result, Seek, SeekFrom, Take, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::no_std::*;
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    /// A specialized [`Result`](core::result::Result) type for I/O operations.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// General categories of I/O error.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        /// An operation could not be completed because the end of a stream was reached.
+        UnexpectedEof,
+        /// An operation could not be completed because a write returned `Ok(0)`.
+        WriteZero,
+        /// A parameter was incorrect, for instance a seek to a negative position.
+        InvalidInput,
+        /// Any other error.
+        Other,
+    }
+
+    /// The error type for I/O operations, mirroring [`std::io::Error`].
+    pub struct Error {
+        kind: ErrorKind,
+        error: Option<Box<dyn core::error::Error + Send + Sync>>,
+    }
+
+    impl Error {
+        /// Create a new error from a kind and an arbitrary error payload.
+        pub fn new<E>(kind: ErrorKind, error: E) -> Self
+        where
+            E: Into<Box<dyn core::error::Error + Send + Sync>>,
+        {
+            Error {
+                kind,
+                error: Some(error.into()),
+            }
+        }
+
+        /// Create a new error of kind [`ErrorKind::Other`] from an arbitrary error payload.
+        pub fn other<E>(error: E) -> Self
+        where
+            E: Into<Box<dyn core::error::Error + Send + Sync>>,
+        {
+            Self::new(ErrorKind::Other, error)
+        }
+
+        /// Return the kind of this error.
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+
+        /// Return a reference to the inner error, if any.
+        pub fn get_ref(&self) -> Option<&(dyn core::error::Error + Send + Sync + 'static)> {
+            self.error.as_deref()
+        }
+
+        /// Consume the error, returning its inner error (if any).
+        pub fn into_inner(self) -> Option<Box<dyn core::error::Error + Send + Sync>> {
+            self.error
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Error { kind, error: None }
+        }
+    }
+
+    impl fmt::Debug for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Error")
+                .field("kind", &self.kind)
+                .field("error", &self.error)
+                .finish()
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match &self.error {
+                Some(e) => e.fmt(f),
+                None => fmt::Debug::fmt(&self.kind, f),
+            }
+        }
+    }
+
+    impl core::error::Error for Error {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            self.error.as_ref().and_then(|e| e.source())
+        }
+    }
+
+    /// Reads bytes from a source, mirroring [`std::io::Read`].
+    pub trait Read {
+        /// Pull some bytes from this source into the buffer, returning how many were read.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Read exactly enough bytes to fill `buf`.
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => return Err(ErrorKind::UnexpectedEof.into()),
+                    n => buf = &mut buf[n..],
+                }
+            }
+            Ok(())
+        }
+
+        /// Read all bytes until the end of the source, appending them to `buf`.
+        fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+            let start = buf.len();
+            let mut chunk = [0u8; 256];
+            loop {
+                match self.read(&mut chunk)? {
+                    0 => return Ok(buf.len() - start),
+                    n => buf.extend_from_slice(&chunk[..n]),
+                }
+            }
+        }
+
+        /// Create an adapter which will read at most `limit` bytes from this source.
+        fn take(self, limit: u64) -> Take<Self>
+        where
+            Self: Sized,
+        {
+            Take { inner: self, limit }
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = core::cmp::min(buf.len(), self.len());
+            let (head, tail) = self.split_at(n);
+            buf[..n].copy_from_slice(head);
+            *self = tail;
+            Ok(n)
+        }
+    }
+
+    /// Writes bytes to a sink, mirroring [`std::io::Write`].
+    pub trait Write {
+        /// Write a buffer into this writer, returning how many bytes were written.
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Flush any buffered data to the sink.
+        fn flush(&mut self) -> Result<()>;
+
+        /// Write an entire buffer into this writer.
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => return Err(ErrorKind::WriteZero.into()),
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Possible ways to seek within a stream, mirroring [`std::io::SeekFrom`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SeekFrom {
+        /// Offset from the start of the stream.
+        Start(u64),
+        /// Offset from the end of the stream.
+        End(i64),
+        /// Offset from the current position.
+        Current(i64),
+    }
+
+    /// A stream with a cursor that can be moved, mirroring [`std::io::Seek`].
+    pub trait Seek {
+        /// Seek to an offset in the stream, returning the new position from the start.
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+    }
+
+    impl<S: Seek + ?Sized> Seek for &mut S {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            (**self).seek(pos)
+        }
+    }
+
+    /// Reader adapter which limits the bytes read from an underlying reader.
+    ///
+    /// Returned by [`Read::take`].
+    #[derive(Debug)]
+    pub struct Take<R> {
+        inner: R,
+        limit: u64,
+    }
+
+    impl<R> Take<R> {
+        /// Return the number of bytes that can be read before this instance returns EOF.
+        pub fn limit(&self) -> u64 {
+            self.limit
+        }
+
+        /// Set the number of bytes that can be read before this instance returns EOF.
+        pub fn set_limit(&mut self, limit: u64) {
+            self.limit = limit;
+        }
+
+        /// Get a reference to the underlying reader.
+        pub fn get_ref(&self) -> &R {
+            &self.inner
+        }
+
+        /// Get a mutable reference to the underlying reader.
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.inner
+        }
+
+        /// Consume the `Take`, returning the underlying reader.
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+    }
+
+    impl<R: Read> Read for Take<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let max = core::cmp::min(buf.len() as u64, self.limit) as usize;
+            let n = self.inner.read(&mut buf[..max])?;
+            self.limit -= n as u64;
+            Ok(n)
+        }
+    }
+
+    /// Wraps an in-memory buffer to provide [`Read`], [`Write`] and [`Seek`], mirroring
+    /// [`std::io::Cursor`].
+    #[derive(Debug, Default, Clone)]
+    pub struct Cursor<T> {
+        inner: T,
+        pos: u64,
+    }
+
+    impl<T> Cursor<T> {
+        /// Create a new cursor at the beginning of the provided buffer.
+        pub fn new(inner: T) -> Self {
+            Cursor { inner, pos: 0 }
+        }
+
+        /// Consume the cursor, returning the underlying buffer.
+        pub fn into_inner(self) -> T {
+            self.inner
+        }
+
+        /// Get a reference to the underlying buffer.
+        pub fn get_ref(&self) -> &T {
+            &self.inner
+        }
+
+        /// Get a mutable reference to the underlying buffer.
+        pub fn get_mut(&mut self) -> &mut T {
+            &mut self.inner
+        }
+
+        /// Return the current position of the cursor.
+        pub fn position(&self) -> u64 {
+            self.pos
+        }
+
+        /// Set the position of the cursor.
+        pub fn set_position(&mut self, pos: u64) {
+            self.pos = pos;
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Read for Cursor<T> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let data = self.inner.as_ref();
+            let start = core::cmp::min(self.pos, data.len() as u64) as usize;
+            let n = (&data[start..]).read(buf)?;
+            self.pos += n as u64;
+            Ok(n)
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Seek for Cursor<T> {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            let (base, offset) = match pos {
+                SeekFrom::Start(n) => {
+                    self.pos = n;
+                    return Ok(n);
+                }
+                SeekFrom::End(n) => (self.inner.as_ref().len() as u64, n),
+                SeekFrom::Current(n) => (self.pos, n),
+            };
+            match base.checked_add_signed(offset) {
+                Some(n) => {
+                    self.pos = n;
+                    Ok(n)
+                }
+                None => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid seek to a negative or overflowing position",
+                )),
+            }
+        }
+    }
+
+    fn vec_write(pos: &mut u64, vec: &mut Vec<u8>, buf: &[u8]) -> usize {
+        let start = *pos as usize;
+        if vec.len() < start {
+            vec.resize(start, 0);
+        }
+        let overlap = core::cmp::min(vec.len() - start, buf.len());
+        vec[start..start + overlap].copy_from_slice(&buf[..overlap]);
+        vec.extend_from_slice(&buf[overlap..]);
+        *pos += buf.len() as u64;
+        buf.len()
+    }
+
+    impl Write for Cursor<Vec<u8>> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            Ok(vec_write(&mut self.pos, &mut self.inner, buf))
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Write for Cursor<&mut Vec<u8>> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            Ok(vec_write(&mut self.pos, self.inner, buf))
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+}
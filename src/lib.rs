@@ -2,25 +2,89 @@
 //!
 //! [`Reader`]s read the data contained in calculator variable files, and [`Writer`]s write data to
 //! calculator variable files. The meaning of data in any given file depends on the
-//! [`VariableType`].
+//! [`VariableType`]. The most commonly-used items can all be imported from [`prelude`].
 //!
 //! Refer to the [TI link protocol & file format
 //! guide](https://www.ticalc.org/archives/files/fileinfo/247/24750.html)
 //! for details on file formats.
+//!
+//! ## File layout
+//!
+//! Every variable file written by this crate has the same structure, with all multi-byte values
+//! little-endian:
+//!
+//! | Offset | Length | Contents                                                       |
+//! |--------|--------|----------------------------------------------------------------|
+//! | 0      | 11     | Signature: `**TI83F*` followed by `1A 0A 00`                   |
+//! | 11     | 42     | Comment, padded with spaces (or NULs, if requested)            |
+//! | 53     | 2      | Length of the data section that follows                        |
+//! | 55     | 2      | Entry header length: 13, or 11 without the version and flags   |
+//! | 57     | 2      | Length of the variable data                                    |
+//! | 59     | 1      | [`VariableType`]                                               |
+//! | 60     | 8      | Variable name, padded with NULs                                |
+//! | 68     | 1      | Version                                                        |
+//! | 69     | 1      | Flags: 0x80 if the variable is archived                        |
+//! | 70     | 2      | Length of the variable data, again                             |
+//! | 72     | n      | Variable data                                                  |
+//! | 72 + n | 2      | Checksum                                                       |
+//!
+//! The data section runs from offset 55 up to the checksum, so it is 17 bytes longer than the
+//! variable data; because its length is 16 bits, files can hold at most [`MAX_DATA`] bytes of
+//! variable data. The checksum is the sum of every byte in the data section, wrapping on
+//! overflow so it fits in 16 bits. [`Checksum`] computes it.
+//!
+//! ## `no_std` support
+//!
+//! Disabling the default `std` feature makes the crate `no_std`, though it still requires an
+//! allocator. In that case readers and writers operate on the minimal I/O traits in [`io`]
+//! rather than those from `std::io`, and bundles are not available.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// Tests can use std even when the library doesn't
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
+#[cfg(test)]
+use alloc::{string::ToString, vec::Vec};
 use num_enum::TryFromPrimitive;
 
+#[cfg(feature = "tokio")]
+pub mod async_io;
 #[cfg(feature = "bundles")]
 pub mod bundle;
+mod checksum;
+mod file;
+pub mod group;
+pub mod io;
+pub mod prelude;
 pub mod read;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "detokenize")]
+mod tokens;
 pub mod write;
 
+pub use checksum::Checksum;
+pub use file::VariableFile;
 pub use read::Reader;
+#[cfg(feature = "detokenize")]
+pub use tokens::TokenTable;
 pub use write::Writer;
 
 /// Types of variables
 ///
 /// These values correspond to `*Obj` constants from ti83plus.inc, and match the type byte
 /// stored in the VAT on a calculator.
+///
+/// [`Backup`](VariableType::Backup) data is an image of calculator memory which is read and
+/// written as opaque bytes, without interpretation. Backups made with TI's own software use a
+/// different variable header with three separate data sections, which is not supported.
+///
+/// Variants may be added in future versions as more types are supported, so matches on this type
+/// should include a wildcard arm for types they don't handle. To decode data without matching on
+/// types at all, use [`read::decode_auto`].
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive)]
 pub enum VariableType {
@@ -35,7 +99,7 @@ pub enum VariableType {
     GDB = 8,              // 8xd
     Unknown = 9,
     UnknownEquation = 0xa,
-    NewEquation = 0xb,
+    NewEquation = 0xb, // also 8xy
     Complex = 0xc,     // 8xc
     ComplexList = 0xd, // also 8xl
     Undefined = 0xe,
@@ -43,55 +107,407 @@ pub enum VariableType {
     Zoom = 0x10,       // 8xz (ZSto)
     TableSetup = 0x11, // 8xt (TblRng)
     LCD = 0x12,
-    Backup = 0x13,
-    // AppObj=0x14 never appears in the VAT, and 8xk files use the "flash" format
-    AppVar = 0x15, // 8xv
-    TemporaryProgram = 0x16,
-    Group = 0x17, // 8xg
+    Backup = 0x13, // 8xb
+    // AppObj=0x14 never appears in the VAT, and 8xk files use the "flash" format (read::flash)
+    AppVar = 0x15,           // 8xv
+    TemporaryProgram = 0x16, // also 8xp
+    Group = 0x17,            // 8xg
 }
 
 impl VariableType {
-    fn has_length_prefix(&self) -> bool {
+    /// Return every variable type, in order of their type bytes.
+    pub fn all() -> &'static [VariableType] {
+        use VariableType::*;
+        &[
+            Real,
+            List,
+            Matrix,
+            Equation,
+            String,
+            Program,
+            ProtectedProgram,
+            Picture,
+            GDB,
+            Unknown,
+            UnknownEquation,
+            NewEquation,
+            Complex,
+            ComplexList,
+            Undefined,
+            Window,
+            Zoom,
+            TableSetup,
+            LCD,
+            Backup,
+            AppVar,
+            TemporaryProgram,
+            Group,
+        ]
+    }
+
+    /// Return the type byte that identifies variables of this type in files.
+    ///
+    /// This is the same as casting with `as u8`.
+    pub fn to_byte(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Return the variable type identified by a type byte, or `None` if the byte does not
+    /// correspond to a known type.
+    ///
+    /// This is the inverse of [`to_byte`](VariableType::to_byte), and is equivalent to the
+    /// [`TryFrom<u8>`] implementation.
+    pub fn from_byte(b: u8) -> Option<Self> {
+        Self::try_from(b).ok()
+    }
+
+    /// Return whether data for a variable of this type begins with a 16-bit length.
+    ///
+    /// Returns `None` for types whose data format is unknown or not used in variable files.
+    fn has_length_prefix(&self) -> Option<bool> {
         use VariableType::*;
         match self {
-            Equation | String | GDB | Program | ProtectedProgram | Picture | Window
-            | TableSetup | AppVar => true,
-            Real | List | Matrix | Complex | ComplexList => false,
-            x => unimplemented!("Format for variable type {:?} is unknown", x),
+            Equation | String | GDB | Program | ProtectedProgram | Picture | Window | Zoom
+            | TableSetup | AppVar | Group => Some(true),
+            // Laid out like equations and programs respectively
+            NewEquation | TemporaryProgram => Some(true),
+            Real | List | Matrix | Complex | ComplexList => Some(false),
+            // Backup data is an opaque image of calculator memory, so it's handled as raw bytes
+            Backup => Some(false),
+            Unknown | UnknownEquation | Undefined | LCD => None,
         }
     }
 
-    /// Return the customary file extension associated with a file of a given variable type.
-    pub fn file_extension(&self) -> &'static str {
+    /// Return the number of bytes in a file of this type other than the variable data.
+    ///
+    /// This is the file header, the (13-byte) variable entry header with the length fields that
+    /// surround it, the checksum and, for types whose data begins with one, the 16-bit length
+    /// prefix that [`Writer`] adds. A file containing `n` bytes of data is `n + data_overhead()`
+    /// bytes long, and the writer fails once the data and any length prefix exceed [`MAX_DATA`].
+    ///
+    /// Returns `None` for types that can't be stored in variable files.
+    pub fn data_overhead(&self) -> Option<usize> {
+        let prefix_len = if self.has_length_prefix()? { 2 } else { 0 };
+        Some(FILE_HEADER_LEN + DATA_SECTION_OVERHEAD as usize + 2 + prefix_len)
+    }
+
+    /// Return whether variables of this type can be stored in archive memory.
+    ///
+    /// Per the TI link guide, system variables (equations, window and table settings and the
+    /// like) always live in RAM, as do temporary and undefined variables. Groups can only exist
+    /// in archive, so they are archivable.
+    pub fn is_archivable(&self) -> bool {
+        use VariableType::*;
+        match self {
+            Real | List | Matrix | String | Program | ProtectedProgram | Picture | GDB
+            | Complex | ComplexList | AppVar | Group => true,
+            Equation | Unknown | UnknownEquation | NewEquation | Undefined | Window | Zoom
+            | TableSetup | LCD | Backup | TemporaryProgram => false,
+        }
+    }
+
+    /// Return whether variables of this type are listed in the calculator's memory management
+    /// menus, and can ordinarily be created and deleted by users.
+    pub fn is_user_visible(&self) -> bool {
         use VariableType::*;
         match self {
+            Real | List | Matrix | Equation | String | Program | ProtectedProgram | Picture
+            | GDB | Complex | ComplexList | AppVar | Group => true,
+            Unknown | UnknownEquation | NewEquation | Undefined | Window | Zoom | TableSetup
+            | LCD | Backup | TemporaryProgram => false,
+        }
+    }
+
+    /// Return the customary file extension associated with a file of a given variable type.
+    ///
+    /// Returns `None` for types that don't have a known file extension, usually because they
+    /// are not normally stored in files.
+    pub fn file_extension(&self) -> Option<&'static str> {
+        use VariableType::*;
+        Some(match self {
             Real => "8xn",
             Complex => "8xc",
             List | ComplexList => "8xl",
             Matrix => "8xm",
-            Equation => "8xy",
+            Equation | NewEquation => "8xy",
             String => "8xs",
-            Program | ProtectedProgram => "8xp",
+            Program | ProtectedProgram | TemporaryProgram => "8xp",
             Picture => "8xi",
             GDB => "8xd",
+            Window => "8xw",
             Zoom => "8xz",
             TableSetup => "8xt",
+            Backup => "8xb",
             AppVar => "8xv",
             Group => "8xg",
-            t => todo!("File extension for type {:?} isn't yet known", t),
-        }
+            Unknown | UnknownEquation | Undefined | LCD => return None,
+        })
+    }
+
+    /// Return the variable type customarily associated with a file extension.
+    ///
+    /// This is the inverse of [`file_extension`](VariableType::file_extension), and ignores
+    /// case. Some extensions are shared by several types, in which case the most common type is
+    /// returned: `8xl` is a [`List`](VariableType::List) (not a
+    /// [`ComplexList`](VariableType::ComplexList)) and `8xp` is a
+    /// [`Program`](VariableType::Program) (not a
+    /// [`ProtectedProgram`](VariableType::ProtectedProgram) or
+    /// [`TemporaryProgram`](VariableType::TemporaryProgram)), while `8xy` is an
+    /// [`Equation`](VariableType::Equation) rather than a
+    /// [`NewEquation`](VariableType::NewEquation).
+    pub fn from_extension(ext: &str) -> Option<VariableType> {
+        use VariableType::*;
+        Some(match ext.to_ascii_lowercase().as_str() {
+            "8xn" => Real,
+            "8xc" => Complex,
+            "8xl" => List,
+            "8xm" => Matrix,
+            "8xy" => Equation,
+            "8xs" => String,
+            "8xp" => Program,
+            "8xi" => Picture,
+            "8xd" => GDB,
+            "8xw" => Window,
+            "8xz" => Zoom,
+            "8xt" => TableSetup,
+            "8xb" => Backup,
+            "8xv" => AppVar,
+            "8xg" => Group,
+            _ => return None,
+        })
+    }
+}
+
+/// Formats the human-readable name of the type, such as "Protected program".
+impl core::fmt::Display for VariableType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use VariableType::*;
+        f.pad(match self {
+            Real => "Real",
+            List => "List",
+            Matrix => "Matrix",
+            Equation => "Equation",
+            String => "String",
+            Program => "Program",
+            ProtectedProgram => "Protected program",
+            Picture => "Picture",
+            GDB => "Graph database",
+            Unknown => "Unknown",
+            UnknownEquation => "Unknown equation",
+            NewEquation => "New equation",
+            Complex => "Complex",
+            ComplexList => "Complex list",
+            Undefined => "Undefined",
+            Window => "Window settings",
+            Zoom => "Saved window",
+            TableSetup => "Table setup",
+            LCD => "LCD",
+            Backup => "Backup",
+            AppVar => "Application variable",
+            TemporaryProgram => "Temporary program",
+            Group => "Group",
+        })
     }
 }
 
-/// The maximum amount of data that can be stored in a file.
+/// Calculator models, as identified by file signatures.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Model {
+    /// The TI-83 Plus family, including the TI-84 Plus and CE. Files begin with `**TI83F*`.
+    TI83Plus,
+    /// The TI-85, whose files begin with `**TI85**`.
+    TI85,
+    /// The TI-86, whose files begin with `**TI86**`.
+    TI86,
+    /// The TI-89 and TI-89 Titanium, whose files begin with `**TI89**`.
+    ///
+    /// Like the TI-92 models, these have a 68000 processor and a substantially different file
+    /// format.
+    TI89,
+    /// The TI-92, whose files begin with `**TI92**`.
+    TI92,
+    /// The TI-92 Plus and Voyage 200, whose files begin with `**TI92P*`.
+    TI92Plus,
+}
+
+impl Model {
+    /// Return the model corresponding to the first 8 bytes of a file, if known.
+    pub fn from_magic(magic: &[u8; 8]) -> Option<Model> {
+        Some(match magic {
+            b"**TI83F*" => Model::TI83Plus,
+            b"**TI85**" => Model::TI85,
+            b"**TI86**" => Model::TI86,
+            b"**TI89**" => Model::TI89,
+            b"**TI92**" => Model::TI92,
+            b"**TI92P*" => Model::TI92Plus,
+            _ => return None,
+        })
+    }
+}
+
+/// Length of the file header: signature, comment and data section length.
+const FILE_HEADER_LEN: usize = 55;
+
+/// Length of the data section other than the variable data: the 13-byte variable entry header,
+/// its length and the two copies of the data length.
+const DATA_SECTION_OVERHEAD: u16 = 17;
+
+/// The maximum amount of variable data that can be stored in a file.
+///
+/// The data section of a file contains 17 bytes besides the variable data (the variable entry
+/// header and the length fields around it), and the data section's length is stored in 16 bits,
+/// so any more than `u16::MAX - 17` bytes of data overflows the mandatory length fields. See
+/// [the file layout](crate#file-layout) for details.
+///
+/// For types whose data begins with a length prefix the prefix counts towards this limit, so
+/// 2 fewer bytes can follow it.
 ///
-/// Variable data has 17 bytes of overhead and the overall data section size is 16 bits, so any more
-/// than this overflows the mandatory length fields.
-const MAX_DATA: u16 = u16::MAX - 17;
+/// ```
+/// assert_eq!(tifiles::MAX_DATA, 65518);
+/// ```
+pub const MAX_DATA: u16 = u16::MAX - DATA_SECTION_OVERHEAD;
+
+#[test]
+fn format_tables_are_total() {
+    for &ty in VariableType::all() {
+        // None of these may panic
+        let _ = ty.has_length_prefix();
+        let _ = ty.file_extension();
+        let _ = (ty.is_archivable(), ty.is_user_visible());
+        assert!(!ty.to_string().is_empty());
+    }
+}
+
+#[test]
+fn overhead_matches_written_files() {
+    use crate::io::{Cursor, Write};
+
+    for ty in [
+        VariableType::AppVar,
+        VariableType::Program,
+        VariableType::Real,
+    ] {
+        let mut w = Writer::new(Cursor::new(Vec::new()), ty, "A", false).unwrap();
+        w.write_all(&[0; 9]).unwrap();
+        let file = w.close().unwrap().into_inner();
+        assert_eq!(file.len(), 9 + ty.data_overhead().unwrap(), "{:?}", ty);
+    }
+    assert_eq!(VariableType::AppVar.data_overhead(), Some(76));
+    assert_eq!(VariableType::LCD.data_overhead(), None);
+}
+
+#[test]
+fn type_bytes_round_trip() {
+    let mut count = 0;
+    for b in 0..=u8::MAX {
+        if let Some(ty) = VariableType::from_byte(b) {
+            assert_eq!(ty.to_byte(), b);
+            count += 1;
+        }
+    }
+    assert_eq!(count, VariableType::all().len());
+    assert!(VariableType::all()
+        .windows(2)
+        .all(|w| w[0].to_byte() < w[1].to_byte()));
+    assert_eq!(VariableType::AppVar.to_byte(), 0x15);
+    assert_eq!(VariableType::from_byte(0x05), Some(VariableType::Program));
+    assert_eq!(VariableType::from_byte(0x18), None);
+}
+
+#[test]
+fn extensions_round_trip() {
+    use VariableType::*;
+
+    for ty in [
+        Real, Complex, List, Matrix, Equation, String, Program, Picture, GDB, Window, Zoom,
+        TableSetup, Backup, AppVar, Group,
+    ] {
+        assert_eq!(
+            VariableType::from_extension(ty.file_extension().unwrap()),
+            Some(ty)
+        );
+    }
+    assert_eq!(VariableType::from_extension("8XP"), Some(Program));
+    assert_eq!(VariableType::from_extension("8xl"), Some(List));
+    assert_eq!(VariableType::from_extension("txt"), None);
+}
+
+#[test]
+fn display_names() {
+    assert_eq!(
+        VariableType::ProtectedProgram.to_string(),
+        "Protected program"
+    );
+    assert_eq!(VariableType::Backup.to_string(), "Backup");
+    assert_eq!(format!("{:>8}", VariableType::LCD), "     LCD");
+}
+
+#[test]
+fn theta_name_round_trips() {
+    use crate::io::Cursor;
+
+    let mut file_data = vec![];
+    let writer = Writer::new(
+        Cursor::new(&mut file_data),
+        VariableType::AppVar,
+        "\u{03b8}ETA",
+        false,
+    )
+    .unwrap();
+    writer.close().unwrap();
+
+    let reader = Reader::new(&*file_data).unwrap();
+    assert_eq!(reader.name(), b"\x5bETA\0\0\0\0");
+    assert_eq!(reader.name_str(), "\u{03b8}ETA");
+}
+
+/// Backups are read and written as opaque data.
+#[test]
+fn backups_round_trip() {
+    use crate::io::{Cursor, Read, Write};
+
+    let backup: Vec<u8> = (0..1000u32).map(|x| (x * 7 % 251) as u8).collect();
+    let mut w = Writer::new(
+        Cursor::new(Vec::new()),
+        VariableType::Backup,
+        "BACKUP",
+        false,
+    )
+    .unwrap();
+    w.write_all(&backup).unwrap();
+    let file = w.close().unwrap().into_inner();
+
+    let mut reader = Reader::new(&file[..]).unwrap();
+    assert_eq!(reader.ty(), VariableType::Backup);
+    assert_eq!(reader.len(), 1000);
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, backup);
+    reader.finish().unwrap().expect("checksum should be valid");
+}
+
+#[test]
+fn temporary_programs_and_new_equations_round_trip() {
+    use crate::io::{Cursor, Read, Write};
+
+    for ty in [VariableType::TemporaryProgram, VariableType::NewEquation] {
+        let mut w = Writer::new(Cursor::new(Vec::new()), ty, "TMP", false).unwrap();
+        w.write_all(b"\x58\x70\x31").unwrap();
+        let file = w.close().unwrap().into_inner();
+
+        let mut reader = Reader::new(&file[..]).unwrap();
+        assert_eq!(reader.ty(), ty);
+        assert_eq!(reader.name_str(), "TMP");
+        let mut data = vec![];
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"\x58\x70\x31");
+        reader.finish().unwrap().expect("checksum should be valid");
+    }
+}
 
 #[test]
 fn round_trip_is_lossless() {
-    use std::io::{Cursor, Read, Write};
+    use crate::io::{Cursor, Read, Write};
 
     let mut ref_data = [0u8; 256];
     for (x, b) in ref_data.iter_mut().enumerate() {
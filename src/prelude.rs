@@ -0,0 +1,41 @@
+//! Commonly-used types and functions, for glob importing.
+//!
+//! ```
+//! use tifiles::prelude::*;
+//!
+//! let mut writer = Writer::new(tifiles::io::Cursor::new(Vec::new()), VariableType::Real, "A", false)?;
+//! writer.write_real(1.5)?;
+//! let file = writer.close()?.into_inner();
+//!
+//! let data = Reader::new(&file[..])?.read_all_data()?;
+//! assert_eq!(decode_real(&data), Ok(1.5));
+//! # Ok::<(), tifiles::io::Error>(())
+//! ```
+//!
+//! Bundle types are renamed to avoid conflicting with the variable file [`Reader`] and
+//! [`Writer`], so [`bundle::Writer`](crate::bundle::Writer) is available as `BundleWriter`.
+
+pub use crate::read::{
+    decode_auto, decode_complex, decode_complex_list, decode_list, decode_matrix, decode_picture,
+    decode_real, decode_table_setup, decode_window, DecodeError, DecodedVariable, EquationSlot,
+    Matrix, Picture, PictureModel, ReadError, RealIter, TableSetup, VariableIter, WindowSettings,
+};
+pub use crate::write::{
+    encode_complex, encode_complex_list, encode_real, encode_table_setup, encode_window,
+    BufferedWriter, CreateError, EncodeError, WriteError, WriterBuilder,
+};
+pub use crate::{Checksum, Model, Reader, VariableFile, VariableType, Writer, MAX_DATA};
+
+#[cfg(feature = "bundles")]
+pub use crate::bundle::{
+    Builder as BundleBuilder, BundleError, Kind as BundleKind, Reader as BundleReader,
+    Writer as BundleWriter,
+};
+#[cfg(feature = "detokenize")]
+pub use crate::read::{
+    decode_auto_detokenized, decode_equation, decode_string, tokens::detokenize,
+};
+#[cfg(feature = "detokenize")]
+pub use crate::write::tokens::tokenize;
+#[cfg(feature = "detokenize")]
+pub use crate::TokenTable;
@@ -1,22 +1,99 @@
-use std::io::{Error, Read};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 
-use super::VariableType;
+use crate::io::{Cursor, Error, Read, Seek, SeekFrom};
+
+use super::{Checksum, Model, VariableType};
+
+mod auto;
+mod decode;
+mod equation;
+pub mod flash;
+pub(crate) mod group;
+#[cfg(feature = "detokenize")]
+pub mod tokens;
+
+#[cfg(feature = "detokenize")]
+pub use auto::decode_auto_detokenized;
+pub use auto::{decode_auto, DecodedVariable};
+pub(crate) use decode::COMPLEX_FLAG;
+pub use decode::{
+    decode_complex, decode_complex_list, decode_list, decode_matrix, decode_picture, decode_real,
+    decode_table_setup, decode_window, DecodeError, Matrix, Picture, PictureModel, RealIter,
+    TableSetup, WindowSettings, COMPLEX_SIZE, REAL_SIZE, TABLE_SETUP_SIZE, WINDOW_SIZE,
+};
+pub use equation::EquationSlot;
+pub use group::GroupMember;
+#[cfg(feature = "detokenize")]
+pub use tokens::{decode_equation, decode_string};
 
 #[derive(thiserror::Error, Debug)]
 pub enum ReadError {
     #[error("File signature should be (\"**TI83F*\", 1a, 0a, 0), but was {0:?}")]
     InvalidSignature([u8; 11]),
+    #[error("File signature is for a {0:?}, which is not supported")]
+    UnsupportedModel(Model),
     #[error("Variable header reports length {0}, which is unrecognized")]
     UnknownHeaderLength(u16),
     #[error("Variable data length fields disagree: {0} != {1}")]
     DataLengthMismatch(u16, u16),
     #[error("Variable type {0:#x} is not recognized")]
     UnrecognizedType(u8),
+    #[error("Variable type {0:?} is not supported in variable files")]
+    UnsupportedType(VariableType),
+    #[error("Variable type {0:?} is not a group")]
+    NotGroup(VariableType),
+    #[error("Variable file is malformed: {0}")]
+    Malformed(&'static str),
+    #[error("Group data is malformed: {0}")]
+    MalformedGroup(&'static str),
+    #[error("Variable name {0:?} contains illegal characters")]
+    InvalidName([u8; 8]),
+    #[error("File ended while reading {at} ({expected} bytes)")]
+    Truncated { expected: usize, at: &'static str },
+    #[error("File checksum {read:#06x} does not match computed checksum {computed:#06x}")]
+    InvalidChecksum { computed: u16, read: u16 },
+    #[error("Checksum cannot be verified after seeking within variable data")]
+    ChecksumUnavailable,
+    #[error(
+        "File is a flash file (such as an application), which must be read with a FlashReader"
+    )]
+    FlashFile,
+    #[error("Flash file signature should be \"**TIFL**\", but was {0:?}")]
+    NotFlash([u8; 8]),
 }
 
-impl Into<std::io::Error> for ReadError {
-    fn into(self) -> Error {
-        std::io::Error::new(std::io::ErrorKind::Other, self)
+impl From<ReadError> for crate::io::Error {
+    fn from(e: ReadError) -> Error {
+        match e {
+            // Truncation keeps the kind that read_exact would have returned
+            ReadError::Truncated { .. } => Error::new(crate::io::ErrorKind::UnexpectedEof, e),
+            _ => Error::other(e),
+        }
+    }
+}
+
+/// The signature at the beginning of every variable file.
+const SIGNATURE: &[u8; 11] = b"**TI83F*\x1a\x0a\0";
+
+/// Verify that a file signature is one that can be read.
+///
+/// Returns [`ReadError::UnsupportedModel`] for signatures of other known calculator models,
+/// [`ReadError::FlashFile`] for flash files and [`ReadError::InvalidSignature`] for anything else.
+pub(crate) fn check_signature(signature: &[u8; 11]) -> Result<(), ReadError> {
+    if signature == SIGNATURE {
+        return Ok(());
+    }
+    if signature.starts_with(flash::SIGNATURE) {
+        return Err(ReadError::FlashFile);
+    }
+
+    let mut magic = [0u8; 8];
+    magic.copy_from_slice(&signature[..8]);
+    match Model::from_magic(&magic) {
+        Some(model) if model != Model::TI83Plus => Err(ReadError::UnsupportedModel(model)),
+        _ => Err(ReadError::InvalidSignature(*signature)),
     }
 }
 
@@ -24,110 +101,320 @@ pub struct Reader<R>
 where
     R: Read,
 {
-    input: ChecksumReader<std::io::Take<R>>,
-    comment: [u8; 42],
+    input: ChecksumReader<crate::io::Take<R>>,
+    /// The signature, comment and data section length, as they appear in the file
+    file_header: [u8; 55],
+    /// The variable entry header as it appears in the file
+    entry_header: Vec<u8>,
     ty: VariableType,
     name: [u8; 8],
     archived: bool,
+    version: u8,
     data_len: u16,
+    /// The length prefix at the start of the data, for types that have one
+    embedded_len: Option<u16>,
+    /// Header fields as they appear in the file
+    data_section_len: u16,
+    entry_header_len: u16,
+    declared_data_len: u16,
+    /// The checksum stored in the file, once it has been read.
+    file_checksum: Option<u16>,
+    /// Whether the data has been read out of order, making the computed checksum meaningless.
+    seeked: bool,
+}
+
+/// Return whether variables of the given type are named with plain characters rather than
+/// tokens.
+fn has_text_name(ty: VariableType) -> bool {
+    matches!(ty, VariableType::Real | VariableType::Complex) || group::has_named_entry(ty)
+}
+
+/// Return whether a name read from a file would be accepted by [`crate::write::encode_name`].
+fn is_legal_name(name: &[u8; 8]) -> bool {
+    let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    let (chars, padding) = name.split_at(len);
+    let legal_chars = chars
+        .iter()
+        .enumerate()
+        .all(|(i, &b)| b.is_ascii_uppercase() || b == 0x5b || (i > 0 && b.is_ascii_digit()));
+    legal_chars && padding.iter().all(|&b| b == 0)
 }
 
-fn read8<R: Read>(mut r: R) -> std::io::Result<u8> {
+/// Fill `buf` from the input, returning [`ReadError::Truncated`] if the input ends first.
+///
+/// `field` names what is being read, for the error.
+fn read_field<R: Read>(mut r: R, buf: &mut [u8], field: &'static str) -> crate::io::Result<()> {
+    r.read_exact(buf).map_err(|e| match e.kind() {
+        crate::io::ErrorKind::UnexpectedEof => ReadError::Truncated {
+            expected: buf.len(),
+            at: field,
+        }
+        .into(),
+        _ => e,
+    })
+}
+
+fn read8<R: Read>(r: R, field: &'static str) -> crate::io::Result<u8> {
     let mut buf = [0u8];
-    r.read_exact(&mut buf)?;
+    read_field(r, &mut buf, field)?;
     Ok(buf[0])
 }
 
-fn read16<R: Read>(mut r: R) -> std::io::Result<u16> {
+fn read16<R: Read>(r: R, field: &'static str) -> crate::io::Result<u16> {
     let mut buf = [0u8; 2];
-    r.read_exact(&mut buf)?;
+    read_field(r, &mut buf, field)?;
     Ok(u16::from_le_bytes(buf))
 }
 
+/// Split `n` bytes off the front of `data`, returning [`ReadError::Truncated`] if it ends first.
+///
+/// This is the in-memory equivalent of [`read_field`].
+fn take_field<'a>(
+    data: &mut &'a [u8],
+    n: usize,
+    field: &'static str,
+) -> Result<&'a [u8], ReadError> {
+    if data.len() < n {
+        return Err(ReadError::Truncated {
+            expected: n,
+            at: field,
+        });
+    }
+    let (head, tail) = data.split_at(n);
+    *data = tail;
+    Ok(head)
+}
+
+fn take16(data: &mut &[u8], field: &'static str) -> Result<u16, ReadError> {
+    let b = take_field(data, 2, field)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+/// Check that a variable entry header length can be read.
+///
+/// If `lenient`, lengths longer than 13 bytes are accepted.
+fn check_entry_header_len(entry_header_len: u16, lenient: bool) -> Result<(), ReadError> {
+    let known_len = [11, 13].contains(&entry_header_len);
+    if !(known_len || lenient && entry_header_len > 13) {
+        return Err(ReadError::UnknownHeaderLength(entry_header_len));
+    }
+    Ok(())
+}
+
+/// Check that the data section length matches the lengths in the variable entry header.
+fn check_section_len(
+    data_section_len: u16,
+    entry_header_len: u16,
+    data_len: u16,
+) -> Result<(), ReadError> {
+    // Lengths come from untrusted input, so this may overflow
+    let expected_section_len = entry_header_len
+        .checked_add(4)
+        .and_then(|n| data_len.checked_add(n))
+        .ok_or(ReadError::Malformed(
+            "variable data length exceeds the maximum",
+        ))?;
+    if expected_section_len != data_section_len {
+        return Err(ReadError::DataLengthMismatch(
+            expected_section_len,
+            data_section_len,
+        ));
+    }
+    Ok(())
+}
+
+/// Check that the length prefix of a variable agrees with its data length.
+fn check_length_prefix(data_len: u16, inner_len: u16) -> Result<(), ReadError> {
+    // Inner length excludes the length field itself
+    if inner_len.checked_add(2) != Some(data_len) {
+        return Err(ReadError::DataLengthMismatch(data_len, inner_len));
+    }
+    Ok(())
+}
+
 impl<R> Reader<R>
 where
     R: Read,
 {
-    pub fn new(mut r: R) -> std::io::Result<Self> {
-        let mut signature = [0u8; 11];
-        r.read_exact(&mut signature)?;
-        if &signature != b"**TI83F*\x1a\x0a\0" {
-            return Err(ReadError::InvalidSignature(signature).into());
-        }
+    pub fn new(r: R) -> crate::io::Result<Self> {
+        Self::open(r, true, false)
+    }
+
+    /// Read a variable file without computing its checksum.
+    ///
+    /// This is faster than [`new`](Reader::new) for inputs that are trusted not to be corrupt,
+    /// since the data doesn't need to be summed as it is read. Headers are validated in the same
+    /// way, but [`finish`](Reader::finish) always succeeds without checking the stored checksum
+    /// and [`verify`](Reader::verify) fails with [`ReadError::ChecksumUnavailable`].
+    pub fn new_unverified(r: R) -> crate::io::Result<Self> {
+        Self::open(r, false, false)
+    }
 
-        let mut comment = [0u8; 42];
-        r.read_exact(&mut comment)?;
+    /// Read a variable file, accepting variable entry headers longer than usual.
+    ///
+    /// Some software writes files with a variable header longer than the standard 13 bytes. This
+    /// behaves like [`new`](Reader::new), but for such files reads the standard fields and skips
+    /// the rest of the header instead of returning [`ReadError::UnknownHeaderLength`]. The skipped
+    /// bytes are still included in [`raw_entry_header`](Reader::raw_entry_header).
+    ///
+    /// The extra bytes may contain information that changes the meaning of the variable, and will
+    /// be lost if the variable is copied to a new file, so this is best used only for files known
+    /// to come from such software.
+    pub fn new_lenient(r: R) -> crate::io::Result<Self> {
+        Self::open(r, true, true)
+    }
+
+    /// Read the headers of a variable file.
+    ///
+    /// If `lenient`, entry headers longer than 13 bytes are accepted.
+    fn open(mut r: R, verify: bool, lenient: bool) -> crate::io::Result<Self> {
+        let mut file_header = [0u8; 55];
+        read_field(&mut r, &mut file_header[..11], "signature")?;
+        check_signature(file_header[..11].try_into().unwrap())?;
 
-        let data_section_len = read16(&mut r)?;
+        read_field(&mut r, &mut file_header[11..53], "comment")?;
+        read_field(&mut r, &mut file_header[53..], "data section length")?;
+        let data_section_len = u16::from_le_bytes([file_header[53], file_header[54]]);
 
         // Begin data section. All data from here until final checksum is checksummed,
         // and the data section length tells us how much data we can read.
         let mut r = ChecksumReader {
             r: r.take(data_section_len as u64),
-            checksum: 0,
+            checksum: Checksum::new(),
+            verify,
         };
 
-        let entry_header_len = read16(&mut r)?;
-        if ![11, 13].contains(&entry_header_len) {
-            return Err(ReadError::UnknownHeaderLength(entry_header_len).into());
-        }
+        let entry_header_len = read16(&mut r, "variable header length")?;
+        check_entry_header_len(entry_header_len, lenient)?;
 
-        let mut data_len = read16(&mut r)?;
-        if data_len + entry_header_len + 4 != data_section_len {
-            return Err(ReadError::DataLengthMismatch(
-                data_len + entry_header_len + 4,
-                data_section_len,
-            )
-            .into());
-        }
+        let mut data_len = read16(&mut r, "data length")?;
+        check_section_len(data_section_len, entry_header_len, data_len)?;
 
-        let ty = match VariableType::try_from(read8(&mut r)?) {
-            Ok(ty) => ty,
-            Err(e) => return Err(ReadError::UnrecognizedType(e.number).into()),
-        };
+        let ty = read8(&mut r, "variable type")?;
+        let ty = VariableType::from_byte(ty).ok_or(ReadError::UnrecognizedType(ty))?;
 
         let mut name = [0u8; 8];
-        r.read_exact(&mut name)?;
+        read_field(&mut r, &mut name, "variable name")?;
 
-        let archived = if entry_header_len == 13 {
-            let _version = read8(&mut r)?;
-            let flag = read8(&mut r)?;
-            flag & 0x80 != 0
+        let (version, flags) = if entry_header_len >= 13 {
+            let version = read8(&mut r, "version")?;
+            let flags = read8(&mut r, "archive flag")?;
+            (version, Some(flags))
         } else {
-            false
+            (0, None)
         };
+        let mut extra = vec![0u8; entry_header_len.saturating_sub(13) as usize];
+        read_field(&mut r, &mut extra, "variable header")?;
+        let archived = flags.is_some_and(|f| f & 0x80 != 0);
 
-        let data_len2 = read16(&mut r)?;
+        let data_len2 = read16(&mut r, "data length")?;
         if data_len != data_len2 {
             return Err(ReadError::DataLengthMismatch(data_len, data_len2).into());
         }
 
-        if ty.has_length_prefix() {
-            // Inner length excludes the length field itself
-            let inner_len = read16(&mut r)?;
-            if data_len != inner_len + 2 {
-                return Err(ReadError::DataLengthMismatch(data_len, inner_len).into());
-            }
+        // Everything has been validated, so the header can be reassembled exactly as it was read
+        let mut entry_header = Vec::with_capacity(entry_header_len as usize + 4);
+        entry_header.extend_from_slice(&entry_header_len.to_le_bytes());
+        entry_header.extend_from_slice(&data_len.to_le_bytes());
+        entry_header.push(ty.to_byte());
+        entry_header.extend_from_slice(&name);
+        if let Some(flags) = flags {
+            entry_header.extend_from_slice(&[version, flags]);
+        }
+        entry_header.extend_from_slice(&extra);
+        entry_header.extend_from_slice(&data_len2.to_le_bytes());
+
+        let declared_data_len = data_len;
+        let has_length_prefix = match ty.has_length_prefix() {
+            Some(x) => x,
+            None => return Err(ReadError::UnsupportedType(ty).into()),
+        };
+        let mut embedded_len = None;
+        if has_length_prefix {
+            let inner_len = read16(&mut r, "length prefix")?;
+            check_length_prefix(data_len, inner_len)?;
             // Reported length excludes the length prefix because we handle that
             data_len -= 2;
+            embedded_len = Some(inner_len);
         }
 
-        debug_assert_eq!(
-            r.r.limit(),
-            data_len as u64,
-            "remaining data to take should be equal to var data size"
-        );
+        // The checks above should guarantee that exactly the variable data remains in the data
+        // section, but reads are bounded by the section length rather than the data length so
+        // make sure they agree even if the header is laid out unexpectedly.
+        let remaining = r.r.limit();
+        if remaining != data_len as u64 {
+            return Err(ReadError::DataLengthMismatch(data_len, remaining as u16).into());
+        }
 
         Ok(Reader {
             input: r,
-            comment,
+            file_header,
+            entry_header,
             ty,
             name,
             archived,
+            version,
             data_len,
+            embedded_len,
+            data_section_len,
+            entry_header_len,
+            declared_data_len,
+            file_checksum: None,
+            seeked: false,
         })
     }
 
+    /// Read a variable file, rejecting files with questionable variable names.
+    ///
+    /// This behaves like [`new`](Reader::new), but additionally checks that the variable name
+    /// follows the same rules that [`Writer`](crate::Writer) enforces (uppercase letters, θ, and
+    /// digits after the first character, padded with NULs) and returns
+    /// [`ReadError::InvalidName`] if it does not. Only types with textual names are checked:
+    /// other variables (such as lists and matrices) are named with tokens.
+    pub fn new_strict(r: R) -> crate::io::Result<Self> {
+        let reader = Self::new(r)?;
+        if has_text_name(reader.ty) && !is_legal_name(&reader.name) {
+            return Err(ReadError::InvalidName(reader.name).into());
+        }
+        Ok(reader)
+    }
+
+    /// Return the length of the data section as declared in the file header.
+    ///
+    /// The data section consists of the variable entry header and the variable data, so this is
+    /// the sum of [`entry_header_len`](Reader::entry_header_len),
+    /// [`declared_data_len`](Reader::declared_data_len) and the 4 bytes of the two length fields
+    /// that precede the data.
+    pub fn data_section_len(&self) -> u16 {
+        self.data_section_len
+    }
+
+    /// Return the length of the variable entry header: 13 for files with version and archive flag
+    /// fields, or 11 for older files without them. Files read with
+    /// [`new_lenient`](Reader::new_lenient) may have longer headers.
+    pub fn entry_header_len(&self) -> u16 {
+        self.entry_header_len
+    }
+
+    /// Return the variable data length as declared in the entry header.
+    ///
+    /// The entry header contains two copies of this value, which are required to be equal.
+    /// Unlike [`len`](Reader::len), this includes the length prefix for types that have one.
+    pub fn declared_data_len(&self) -> u16 {
+        self.declared_data_len
+    }
+
+    /// Return the length prefix at the start of the variable data, for types that have one.
+    ///
+    /// The prefix is consumed when the reader is created and isn't included in the data that is
+    /// read, but is exposed here for inspection. Readers require it to be equal to
+    /// [`len`](Reader::len), so files where it disagrees with the declared data length are
+    /// rejected with [`ReadError::DataLengthMismatch`]. Returns `None` for types without a length
+    /// prefix.
+    pub fn embedded_len(&self) -> Option<u16> {
+        self.embedded_len
+    }
+
     /// Return the number of bytes of variable data this reader contains.
     ///
     /// This value is constant for any given input data.
@@ -135,35 +422,223 @@ where
         self.data_len
     }
 
+    /// Return whether the reader contains no variable data.
+    ///
+    /// Variables such as empty programs still have a length prefix in the file, which is not
+    /// counted: this is equivalent to `len() == 0`.
+    pub fn is_empty(&self) -> bool {
+        self.data_len == 0
+    }
+
+    /// Get the calculator model that this file is for.
+    ///
+    /// Only files for the TI-83 Plus family can currently be read, so this is always
+    /// [`Model::TI83Plus`].
+    pub fn model(&self) -> Model {
+        Model::TI83Plus
+    }
+
     /// Get the type of the variable returned via this reader.
     pub fn ty(&self) -> VariableType {
         self.ty
     }
 
+    /// Check that the variable has the expected type, returning the reader if it does.
+    ///
+    /// If the type is different, returns the reader along with the variable's actual type so
+    /// it can still be used:
+    ///
+    /// ```
+    /// # use tifiles::{Reader, VariableType};
+    /// # fn doit<R: tifiles::io::Read>(input: R) -> Option<Reader<R>> {
+    /// let program = match Reader::new(input).ok()?.expect_type(VariableType::Program) {
+    ///     Ok(reader) => reader,
+    ///     Err((_, actual)) => {
+    ///         eprintln!("Expected a program but got {}", actual);
+    ///         return None;
+    ///     }
+    /// };
+    /// # Some(program)
+    /// # }
+    /// ```
+    pub fn expect_type(self, ty: VariableType) -> Result<Self, (Self, VariableType)> {
+        if self.ty == ty {
+            Ok(self)
+        } else {
+            let actual = self.ty;
+            Err((self, actual))
+        }
+    }
+
+    /// Get the version of the variable's format.
+    ///
+    /// Versions other than 0 usually indicate that the variable uses features of newer calculator
+    /// OSes, such as TI-BASIC tokens that older OSes don't support. Files with the older 11-byte
+    /// variable header don't store a version, so it's 0 for them.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
     /// Get the contained variable's name.
     pub fn name(&self) -> &[u8] {
         self.name.as_slice()
     }
 
+    /// Get the contained variable's name as a string.
+    ///
+    /// NUL padding is removed and the θ token (0x5b) is translated to the Unicode θ character,
+    /// reversing the translation applied by [`Writer::new`](crate::Writer::new).
+    pub fn name_str(&self) -> String {
+        self.name
+            .iter()
+            .take_while(|&&b| b != 0)
+            .map(|&b| match b {
+                0x5b => '\u{03b8}',
+                b if b.is_ascii() => b as char,
+                _ => char::REPLACEMENT_CHARACTER,
+            })
+            .collect()
+    }
+
+    /// Read all of the variable's remaining data.
+    ///
+    /// Since the length of the data is known, this reads it into a buffer of exactly the right
+    /// size rather than growing one as [`read_to_end`](Read::read_to_end) does. If no data has
+    /// been read yet this returns [`len`](Reader::len) bytes, and afterwards the reader is
+    /// positioned for [`finish`](Reader::finish) or [`verify`](Reader::verify) to check the
+    /// checksum. Returns [`ReadError::Truncated`] if the input ends before all of the data.
+    pub fn read_all_data(&mut self) -> crate::io::Result<Vec<u8>> {
+        let mut data = vec![0u8; self.input.r.limit() as usize];
+        read_field(&mut self.input, &mut data, "variable data")?;
+        Ok(data)
+    }
+
+    /// Borrow a reader of only the remaining variable data.
+    ///
+    /// This reads the same data as reading from the `Reader` itself, but can be passed by value
+    /// to code that takes any `Read`, such as a parser for the variable's contents, while the
+    /// `Reader` stays available. Data read through it is still added to the checksum, so
+    /// [`finish`](Reader::finish) and [`verify`](Reader::verify) remain valid afterward.
+    pub fn data_reader(&mut self) -> impl Read + '_ {
+        &mut self.input
+    }
+
+    /// Collect the metadata returned by [`peek_header`].
+    fn header(&self) -> Header {
+        Header {
+            ty: self.ty,
+            name: self.name,
+            archived: self.archived,
+            version: self.version,
+            comment: *self.raw_comment(),
+            data_len: self.data_len,
+        }
+    }
+
+    /// Return the slot of an [`Equation`](VariableType::Equation) variable, which is encoded in
+    /// its name.
+    ///
+    /// Returns `None` for other types of variable, or if the name isn't a known equation.
+    pub fn equation_slot(&self) -> Option<EquationSlot> {
+        if self.ty != VariableType::Equation {
+            return None;
+        }
+        EquationSlot::from_name(&self.name)
+    }
+
     /// Return whether the contained variable is marked as archived.
     pub fn is_archived(&self) -> bool {
         self.archived
     }
 
-    /// Return the file's comment.
+    /// Return the file's comment, with trailing NULs removed.
+    ///
+    /// The comment is always read from a 42-byte field following the signature: that's the size
+    /// used by every format this crate reads, and since it's immediately followed by the data
+    /// section length a file with a different comment size would be rejected when that length
+    /// fails to match the data. Comments shorter than 42 bytes are usually padded with NULs,
+    /// which are not included.
     pub fn comment(&self) -> &[u8] {
-        self.comment.as_slice()
+        let comment = self.raw_comment();
+        let len = comment.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        &comment[..len]
     }
 
-    /// Finish reading the input, dropping unread data.
+    /// Return the file's entire 42-byte comment field, including any padding.
+    pub fn raw_comment(&self) -> &[u8; 42] {
+        self.file_header[11..53].try_into().unwrap()
+    }
+
+    /// Return the first 55 bytes of the file exactly as they were read: the signature, comment
+    /// and data section length.
+    pub fn raw_file_header(&self) -> &[u8; 55] {
+        &self.file_header
+    }
+
+    /// Return the variable entry header exactly as it was read.
     ///
-    /// Returns `Ok` if the file checksum is valid, `Err` otherwise. Any data that wasn't read by
-    /// the user is used to verify the checksum but is not returned. If the checksum is not valid,
-    /// the output tuple's first value is the computed checksum and the second is the checksum
-    /// included in the file.
+    /// This is the beginning of the data section, from the header length through the second
+    /// copy of the data length, and is 4 bytes longer than
+    /// [`entry_header_len`](Reader::entry_header_len). Variable data follows it, beginning with
+    /// the length prefix for types that have one.
+    pub fn raw_entry_header(&self) -> &[u8] {
+        &self.entry_header
+    }
+
+    /// Return the file's comment as a string.
     ///
-    /// The reader will be positioned after all file data on success.
-    pub fn finish(mut self) -> std::io::Result<Result<R, FinishError<R>>> {
+    /// Some programs write a NUL-terminated comment without clearing the rest of the field, so
+    /// the comment ends at the first NUL and anything after it is ignored. Trailing spaces are
+    /// also removed.
+    pub fn comment_str(&self) -> String {
+        let comment = self.raw_comment().as_slice();
+        let comment = match comment.iter().position(|&b| b == 0) {
+            Some(nul) => &comment[..nul],
+            None => comment,
+        };
+        let len = comment
+            .iter()
+            .rposition(|&b| b != b' ')
+            .map_or(0, |i| i + 1);
+        String::from_utf8_lossy(&comment[..len]).into_owned()
+    }
+
+    /// Read the members of a group variable.
+    ///
+    /// This reads all of the remaining data from the variable, so it should be called before
+    /// reading any data. Returns [`ReadError::NotGroup`] if the variable is not a
+    /// [`Group`](VariableType::Group), or [`ReadError::MalformedGroup`] if the members can't be
+    /// parsed or their lengths don't add up to the length of the data. The checksum can still be
+    /// verified with [`finish`](Reader::finish) afterward.
+    pub fn group_members(&mut self) -> crate::io::Result<Vec<GroupMember>> {
+        if self.ty != VariableType::Group {
+            return Err(ReadError::NotGroup(self.ty).into());
+        }
+
+        let mut data = Vec::with_capacity(self.data_len as usize);
+        self.read_to_end(&mut data)?;
+        group::parse_members(&data).map_err(Into::into)
+    }
+
+    /// Return the checksum of the data that has been read so far.
+    ///
+    /// Once all data has been read, this is the checksum that the file should contain. Readers
+    /// created with [`new_unverified`](Reader::new_unverified) don't compute a checksum, so this
+    /// is always 0 for them.
+    pub fn computed_checksum(&self) -> u16 {
+        self.input.checksum.finalize()
+    }
+
+    /// Read any remaining data, then read the checksum stored in the file.
+    fn read_file_checksum(&mut self) -> crate::io::Result<u16> {
+        // Unverified readers don't compute a checksum, so seeking can't invalidate it
+        if self.seeked && self.input.verify {
+            return Err(ReadError::ChecksumUnavailable.into());
+        }
+        if let Some(checksum) = self.file_checksum {
+            return Ok(checksum);
+        }
+
         // Read to end of data
         loop {
             let mut buf = [0u8; 256];
@@ -172,66 +647,579 @@ where
             }
         }
 
-        // Stop computing checksums of read data and compare with file
-        let ChecksumReader { r, checksum } = self.input;
-        let mut input = r.into_inner();
-        let file_checksum = read16(&mut input)?;
+        if self.input.r.limit() > 0 {
+            return Err(ReadError::Truncated {
+                expected: self.data_len as usize,
+                at: "variable data",
+            }
+            .into());
+        }
+
+        // Checksum follows the data and is not itself checksummed
+        let checksum = read16(self.input.r.get_mut(), "checksum")?;
+        self.file_checksum = Some(checksum);
+        Ok(checksum)
+    }
 
-        if checksum != file_checksum {
+    /// Verify the file checksum, dropping unread data.
+    ///
+    /// Returns `true` if the checksum is valid. Any data that wasn't read by the user is used to
+    /// verify the checksum but is not returned, and subsequent reads will return no data. The
+    /// underlying reader will be positioned after all file data on success.
+    pub fn verify(&mut self) -> crate::io::Result<bool> {
+        if !self.input.verify {
+            return Err(ReadError::ChecksumUnavailable.into());
+        }
+        Ok(self.read_file_checksum()? == self.input.checksum.finalize())
+    }
+
+    /// Finish reading the input, dropping unread data.
+    ///
+    /// Returns `Ok` if the file checksum is valid, `Err` otherwise. Any data that wasn't read by
+    /// the user is used to verify the checksum but is not returned; use
+    /// [`finish_salvaging`](Reader::finish_salvaging) to keep it if the checksum is not valid.
+    ///
+    /// The reader will be positioned after all file data on success. Readers created with
+    /// [`new_unverified`](Reader::new_unverified) skip over the stored checksum without checking
+    /// it, so they only return errors if the input is truncated.
+    pub fn finish(self) -> crate::io::Result<Result<R, FinishError<R>>> {
+        self.finish_with(Vec::new())
+    }
+
+    /// Finish reading the input like [`finish`](Reader::finish), keeping unread data if the
+    /// checksum is not valid.
+    ///
+    /// Any data that wasn't read by the user is buffered while verifying the checksum, so that
+    /// if it is not valid the [`FinishError`] holds the unread data along with the checksums and
+    /// it can be salvaged. Data that was already read (or discarded by
+    /// [`verify`](Reader::verify)) is not retained, so callers wanting all of the data should
+    /// read it before finishing.
+    pub fn finish_salvaging(mut self) -> crate::io::Result<Result<R, FinishError<R>>> {
+        let mut unread_data = Vec::new();
+        if self.input.verify && !self.seeked && self.file_checksum.is_none() {
+            self.input.read_to_end(&mut unread_data)?;
+        }
+        self.finish_with(unread_data)
+    }
+
+    /// Read the rest of the file and verify its checksum, returning `unread_data` in the error
+    /// if it is not valid.
+    fn finish_with(mut self, unread_data: Vec<u8>) -> crate::io::Result<Result<R, FinishError<R>>> {
+        let file_checksum = self.read_file_checksum()?;
+        let ChecksumReader {
+            r,
+            checksum,
+            verify,
+        } = self.input;
+        let checksum = checksum.finalize();
+        let input = r.into_inner();
+
+        if verify && checksum != file_checksum {
             Ok(Err(FinishError {
                 r: input,
                 computed_checksum: checksum,
                 read_checksum: file_checksum,
+                unread_data,
             }))
         } else {
             Ok(Ok(input))
         }
     }
+
+    /// Abandon reading the file and return the underlying reader.
+    ///
+    /// Unlike [`finish`](Reader::finish), this does not read the remaining data or verify the
+    /// checksum: the returned reader is positioned wherever reading stopped, which may be in the
+    /// middle of the variable data or before the checksum (or after it, if
+    /// [`verify`](Reader::verify) has been called).
+    pub fn into_inner(self) -> R {
+        self.input.r.into_inner()
+    }
+}
+
+impl<'a> Reader<&'a [u8]> {
+    /// Read a variable file held in memory, returning its header and the variable data.
+    ///
+    /// The file is validated in the same way as by [`new`](Reader::new), and its checksum is
+    /// verified, but rather than being copied out with [`Read`] the data is returned as a slice
+    /// of `bytes`. Returns [`ReadError::Truncated`] if `bytes` ends before the checksum, or
+    /// [`ReadError::InvalidChecksum`] if the checksum is wrong. Anything after the checksum is
+    /// ignored.
+    pub fn from_slice(bytes: &'a [u8]) -> crate::io::Result<(Header, &'a [u8])> {
+        Ok(Self::parse_slice(bytes)?)
+    }
+
+    /// Parse a file in memory for [`from_slice`](Reader::from_slice), performing the same checks
+    /// as [`open`](Reader::open) does while reading.
+    fn parse_slice(bytes: &'a [u8]) -> Result<(Header, &'a [u8]), ReadError> {
+        let mut rest = bytes;
+        let signature = take_field(&mut rest, 11, "signature")?;
+        check_signature(signature.try_into().unwrap())?;
+        let comment = take_field(&mut rest, 42, "comment")?;
+        let data_section_len = take16(&mut rest, "data section length")?;
+        let section_start = rest;
+
+        let entry_header_len = take16(&mut rest, "variable header length")?;
+        check_entry_header_len(entry_header_len, false)?;
+        let mut data_len = take16(&mut rest, "data length")?;
+        check_section_len(data_section_len, entry_header_len, data_len)?;
+
+        let ty = take_field(&mut rest, 1, "variable type")?[0];
+        let ty = VariableType::from_byte(ty).ok_or(ReadError::UnrecognizedType(ty))?;
+        let name = take_field(&mut rest, 8, "variable name")?;
+        let (version, archived) = if entry_header_len == 13 {
+            let version = take_field(&mut rest, 1, "version")?[0];
+            let flags = take_field(&mut rest, 1, "archive flag")?[0];
+            (version, flags & 0x80 != 0)
+        } else {
+            (0, false)
+        };
+        let data_len2 = take16(&mut rest, "data length")?;
+        if data_len != data_len2 {
+            return Err(ReadError::DataLengthMismatch(data_len, data_len2));
+        }
+
+        match ty.has_length_prefix() {
+            Some(true) => {
+                let inner_len = take16(&mut rest, "length prefix")?;
+                check_length_prefix(data_len, inner_len)?;
+                data_len -= 2;
+            }
+            Some(false) => {}
+            None => return Err(ReadError::UnsupportedType(ty)),
+        }
+
+        let data = take_field(&mut rest, data_len as usize, "variable data")?;
+        let section = &section_start[..section_start.len() - rest.len()];
+        let read = take16(&mut rest, "checksum")?;
+        let mut checksum = Checksum::new();
+        checksum.update(section);
+        let computed = checksum.finalize();
+        if computed != read {
+            return Err(ReadError::InvalidChecksum { computed, read });
+        }
+
+        let header = Header {
+            ty,
+            name: name.try_into().unwrap(),
+            archived,
+            version,
+            comment: comment.try_into().unwrap(),
+            data_len,
+        };
+        Ok((header, data))
+    }
+}
+
+impl<R: Read + Seek> Reader<R> {
+    /// Check whether the file checksum is valid, without consuming any data.
+    ///
+    /// This reads the rest of the data to compute the checksum like [`verify`](Reader::verify),
+    /// then seeks back to where reading left off so the reader can still be used normally: data
+    /// can be read and the file finished as if the check had never happened. Readers that can't
+    /// seek can use `verify` instead, which discards the remaining data.
+    ///
+    /// Like `verify`, this fails with [`ReadError::ChecksumUnavailable`] if the reader has been
+    /// seeked or does not compute a checksum.
+    pub fn checksum_is_valid(&mut self) -> crate::io::Result<bool> {
+        let position = self.data_len as u64 - self.input.r.limit();
+        let checksum = self.input.checksum;
+        let valid = self.verify()?;
+
+        self.seek(SeekFrom::Start(position))?;
+        // The data before the position is still all that has been checksummed, so reading can
+        // continue without making the checksum unavailable
+        self.input.checksum = checksum;
+        self.seeked = false;
+        Ok(valid)
+    }
+}
+
+/// Seeks within the variable data.
+///
+/// Positions are relative to the start of the variable data, and seeking outside the data
+/// returns an error of kind [`InvalidInput`](crate::io::ErrorKind::InvalidInput).
+///
+/// The checksum can only be computed when data is read in order, so once the position has been
+/// changed by seeking [`verify`](Reader::verify) and [`finish`](Reader::finish) fail with
+/// [`ReadError::ChecksumUnavailable`]. Verify the file before seeking if needed, and use
+/// [`into_inner`](Reader::into_inner) to recover the underlying reader after seeking.
+impl<R: Read + Seek> Seek for Reader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> crate::io::Result<u64> {
+        let len = self.data_len as u64;
+        let current = len - self.input.r.limit();
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => len.checked_add_signed(n),
+            SeekFrom::Current(n) => current.checked_add_signed(n),
+        };
+        let target = match target {
+            Some(n) if n <= len => n,
+            _ => {
+                return Err(Error::new(
+                    crate::io::ErrorKind::InvalidInput,
+                    "seek outside of variable data",
+                ))
+            }
+        };
+
+        if target != current {
+            // The stored checksum has already been read if the file was verified
+            let checksum_len = if self.file_checksum.is_some() { 2 } else { 0 };
+            let delta = target as i64 - current as i64 - checksum_len;
+            self.input.r.get_mut().seek(SeekFrom::Current(delta))?;
+            self.input.r.set_limit(len - target);
+            self.file_checksum = None;
+            self.seeked = true;
+        }
+        Ok(target)
+    }
 }
 
 impl<R: Read> Read for Reader<R> {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    fn read(&mut self, buf: &mut [u8]) -> crate::io::Result<usize> {
         // input is a Take so we can't overread, and checksums include everything:
         // do nothing but delegate to the underlying reader.
         self.input.read(buf)
     }
 }
 
+/// Reads variable data through the underlying reader's buffer.
+///
+/// Data is added to the checksum when it is consumed, so the checksum is the same as if the data
+/// had been read with [`Read`].
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> std::io::BufRead for Reader<R> {
+    fn fill_buf(&mut self) -> crate::io::Result<&[u8]> {
+        self.input.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.input.consume(amt)
+    }
+}
+
 struct ChecksumReader<R> {
     r: R,
-    checksum: u16,
+    checksum: Checksum,
+    /// Whether to update the checksum; if not, this is a plain passthrough.
+    verify: bool,
 }
 
 impl<R> Read for ChecksumReader<R>
 where
     R: Read,
 {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    fn read(&mut self, buf: &mut [u8]) -> crate::io::Result<usize> {
         let n = self.r.read(buf)?;
-        for &b in &buf[..n] {
-            self.checksum = self.checksum.wrapping_add(b as u16);
+        if self.verify {
+            self.checksum.update(&buf[..n]);
         }
         Ok(n)
     }
 }
 
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> std::io::BufRead for ChecksumReader<R> {
+    fn fill_buf(&mut self) -> crate::io::Result<&[u8]> {
+        self.r.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.verify {
+            // Consumed data must already be buffered, so this doesn't need to read anything
+            if let Ok(buf) = self.r.fill_buf() {
+                self.checksum.update(&buf[..amt.min(buf.len())]);
+            }
+        }
+        self.r.consume(amt)
+    }
+}
+
+/// The metadata of a variable file, as returned by [`peek_header`] and
+/// [`Reader::from_slice`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Header {
+    /// The type of the variable.
+    pub ty: VariableType,
+    /// The variable's name, as returned by [`Reader::name`].
+    pub name: [u8; 8],
+    /// Whether the variable is marked for placement in archive.
+    pub archived: bool,
+    /// The version of the variable's format, as returned by [`Reader::version`].
+    pub version: u8,
+    /// The entire file comment field including any padding, as returned by
+    /// [`Reader::raw_comment`].
+    pub comment: [u8; 42],
+    /// The number of bytes of variable data, as returned by [`Reader::len`].
+    pub data_len: u16,
+}
+
+/// Read the metadata of a variable file without taking ownership of the input.
+///
+/// Only the file header is read, so the input is left positioned at the start of the variable
+/// data. The header is validated in the same way as by [`Reader::new`], but the checksum is not
+/// verified since the data isn't read.
+///
+/// ```
+/// # use tifiles::read::peek_header;
+/// # fn doit<R: tifiles::io::Read>(input: &mut R) -> tifiles::io::Result<()> {
+/// let header = peek_header(input)?;
+/// println!("{:?} is {} bytes", header.ty, header.data_len);
+/// # Ok(())
+/// # }
+/// ```
+pub fn peek_header<R: Read>(r: &mut R) -> crate::io::Result<Header> {
+    Ok(Reader::new(r)?.header())
+}
+
+/// Reads a sequence of variable files stored back-to-back in a single stream.
+///
+/// Each file is read into memory in its entirety before being returned, so the underlying stream
+/// is always positioned at the start of the next file. Iteration ends when the stream is exhausted
+/// at a file boundary; if the stream ends partway through a file, an error of kind
+/// [`UnexpectedEof`](crate::io::ErrorKind::UnexpectedEof) is returned instead.
+///
+/// Checksums are not verified by the iterator: use [`Reader::verify`] or [`Reader::finish`] on
+/// each returned reader to do so.
+///
+/// ```
+/// # use tifiles::read::VariableIter;
+/// # fn doit<R: tifiles::io::Read>(input: R) -> tifiles::io::Result<()> {
+/// for var in VariableIter::new(input) {
+///     let var = var?;
+///     println!("{:?} {}", var.ty(), var.name_str());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct VariableIter<R> {
+    r: R,
+    failed: bool,
+}
+
+impl<R: Read> VariableIter<R> {
+    /// Iterate over the variable files in the provided input.
+    pub fn new(r: R) -> Self {
+        VariableIter { r, failed: false }
+    }
+
+    /// Return the underlying reader, positioned after the last file returned.
+    pub fn into_inner(self) -> R {
+        self.r
+    }
+
+    fn read_file(&mut self) -> crate::io::Result<Option<Vec<u8>>> {
+        // Signature, comment and data section length. A clean end of input is only possible
+        // before the first byte.
+        let mut data = vec![0u8; 55];
+        if self.r.read(&mut data[..1])? == 0 {
+            return Ok(None);
+        }
+        read_field(&mut self.r, &mut data[1..], "file header")?;
+        check_signature(data[..11].try_into().unwrap())?;
+
+        // Data section and checksum
+        let data_section_len = u16::from_le_bytes([data[53], data[54]]) as usize;
+        data.resize(55 + data_section_len + 2, 0);
+        read_field(&mut self.r, &mut data[55..], "data section")?;
+        Ok(Some(data))
+    }
+}
+
+impl<R: Read> Iterator for VariableIter<R> {
+    type Item = crate::io::Result<Reader<Cursor<Vec<u8>>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The stream position is unknown after an error, so there's no way to continue
+        if self.failed {
+            return None;
+        }
+        let result = match self.read_file() {
+            Ok(None) => return None,
+            Ok(Some(data)) => Reader::new(Cursor::new(data)),
+            Err(e) => Err(e),
+        };
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("File checksum was {read_checksum:#x} but read data checksummed to {computed_checksum:#x}")]
 pub struct FinishError<R> {
     r: R,
     computed_checksum: u16,
     read_checksum: u16,
+    unread_data: Vec<u8>,
 }
 
 impl<R> FinishError<R> {
     pub fn into_reader(self) -> R {
         self.r
     }
+
+    /// Get the checksum computed from the file data.
+    pub fn computed_checksum(&self) -> u16 {
+        self.computed_checksum
+    }
+
+    /// Get the checksum stored in the file.
+    pub fn read_checksum(&self) -> u16 {
+        self.read_checksum
+    }
+
+    /// Get the variable data that hadn't been read when [`Reader::finish_salvaging`] was called.
+    ///
+    /// This is always empty for errors returned by [`Reader::finish`], which doesn't keep the
+    /// data.
+    pub fn unread_data(&self) -> &[u8] {
+        &self.unread_data
+    }
+
+    /// Consume the error, returning the reader and the data that hadn't been read.
+    pub fn into_parts(self) -> (R, Vec<u8>) {
+        (self.r, self.unread_data)
+    }
+
+    /// Replace the reader in this error.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn with_reader<S>(self, r: S) -> FinishError<S> {
+        FinishError {
+            r,
+            computed_checksum: self.computed_checksum,
+            read_checksum: self.read_checksum,
+            unread_data: self.unread_data,
+        }
+    }
+}
+
+#[test]
+fn comments_exclude_padding() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                                  \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
+    /// Return the comment and comment string read from a file with the given comment field.
+    fn read_comment(comment: &[u8; 42]) -> (Vec<u8>, String) {
+        let mut data = DATA.to_vec();
+        data[11..53].copy_from_slice(comment);
+        let reader = Reader::new(&*data).unwrap();
+        (reader.comment().to_vec(), reader.comment_str())
+    }
+
+    let mut comment = [0u8; 42];
+    comment[..8].copy_from_slice(b"Padded  ");
+    assert_eq!(
+        read_comment(&comment),
+        (b"Padded  ".to_vec(), "Padded".into())
+    );
+
+    // Junk after a terminating NUL isn't part of the comment string
+    comment[..34].copy_from_slice(b"Short\0junk from a previous comment");
+    assert_eq!(
+        read_comment(&comment),
+        (
+            b"Short\0junk from a previous comment".to_vec(),
+            "Short".into()
+        )
+    );
+
+    assert_eq!(read_comment(&[0; 42]), (vec![], String::new()));
+}
+
+#[test]
+fn reads_equation_slots() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x17\0\x0d\0\x06\0\x03\x5e\x10\0\0\0\0\0\0\0\0\x06\0\x04\0\x58\x0d\x70\x32\x95\x01";
+
+    let mut reader = Reader::new(DATA).unwrap();
+    assert_eq!(reader.ty(), VariableType::Equation);
+    assert_eq!(reader.equation_slot(), Some(EquationSlot::Function(1)));
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"\x58\x0d\x70\x32");
+    assert!(reader.verify().unwrap());
+    #[cfg(feature = "detokenize")]
+    assert_eq!(decode_equation(&data), "X²+2");
+
+    // Other types have no slot, even if their name looks like one
+    let mut appvar = DATA.to_vec();
+    appvar[59] = VariableType::AppVar.to_byte();
+    assert_eq!(
+        Reader::new_unverified(&*appvar).unwrap().equation_slot(),
+        None
+    );
+}
+
+#[test]
+fn exposes_raw_headers() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\x7f\x02\0\0\0\xe6\0";
+
+    let reader = Reader::new(DATA).unwrap();
+    assert_eq!(reader.raw_file_header(), &DATA[..55]);
+    assert_eq!(reader.raw_comment(), &DATA[11..53]);
+    // Flags other than the archive bit are kept
+    assert!(!reader.is_archived());
+    assert_eq!(reader.raw_entry_header(), &DATA[55..72]);
+
+    // The short header has no version or flags
+    const SHORT: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                           \x11\0\x0b\0\x02\0\x15A\0\0\0\0\0\0\0\x02\0\0\0\x65\0";
+    let reader = Reader::new(SHORT).unwrap();
+    assert_eq!(reader.raw_entry_header(), &SHORT[55..70]);
+}
+
+#[test]
+fn exposes_raw_header_lengths() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
+
+    let reader = Reader::new(DATA).unwrap();
+    assert_eq!(reader.data_section_len(), 0x13);
+    assert_eq!(reader.entry_header_len(), 0x0d);
+    // The declared length includes the length prefix, unlike len()
+    assert_eq!(reader.declared_data_len(), 2);
+    assert_eq!(reader.len(), 0);
+}
+
+#[test]
+fn embedded_lengths_are_only_for_prefixed_types() {
+    use crate::io::Cursor;
+
+    let mut w =
+        crate::Writer::new(Cursor::new(Vec::new()), VariableType::Real, "A", false).unwrap();
+    w.write_real(1.0).unwrap();
+    let reader = Reader::new(Cursor::new(w.close().unwrap().into_inner())).unwrap();
+    assert_eq!(reader.len(), 9);
+    assert_eq!(reader.embedded_len(), None);
+
+    const EMPTY_APPVAR: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                                  \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
+    assert_eq!(Reader::new(EMPTY_APPVAR).unwrap().embedded_len(), Some(0));
+}
+
+#[test]
+fn read_errors_convert_to_io_errors() {
+    fn fails() -> crate::io::Result<()> {
+        Err(ReadError::ChecksumUnavailable)?
+    }
+    let e = fails().unwrap_err();
+    assert_eq!(e.kind(), crate::io::ErrorKind::Other);
+    assert!(matches!(
+        e.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::ChecksumUnavailable)
+    ));
+
+    let e = crate::io::Error::from(ReadError::Truncated {
+        expected: 2,
+        at: "checksum",
+    });
+    assert_eq!(e.kind(), crate::io::ErrorKind::UnexpectedEof);
 }
 
 #[test]
 fn reads_empty_appvar() {
-    const DATA: &'static [u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
                                   \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
 
     let mut reader = Reader::new(DATA).unwrap();
@@ -250,3 +1238,705 @@ fn reads_empty_appvar() {
 
     reader.finish().unwrap().expect("checksum should be valid");
 }
+
+#[test]
+fn empty_variables_are_empty() {
+    use crate::io::Write;
+
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
+
+    assert!(Reader::new(DATA).unwrap().is_empty());
+
+    let mut w =
+        crate::Writer::new(Cursor::new(Vec::new()), VariableType::AppVar, "A", false).unwrap();
+    w.write_all(b"x").unwrap();
+    let file = w.close().unwrap().into_inner();
+    assert!(!Reader::new(&*file).unwrap().is_empty());
+}
+
+#[test]
+fn reads_names_and_comments_as_strings() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
+
+    let reader = Reader::new(DATA).unwrap();
+    assert_eq!(reader.name_str(), "A");
+    assert_eq!(
+        reader.comment_str(),
+        "Created by SourceCoder 3 - sc.cemetech.net"
+    );
+
+    let w = crate::Writer::new(
+        Cursor::new(Vec::new()),
+        VariableType::Program,
+        "ABC123",
+        false,
+    )
+    .unwrap();
+    let file = w.close().unwrap().into_inner();
+    let reader = Reader::new(&*file).unwrap();
+    assert_eq!(reader.name_str(), "ABC123");
+    assert_eq!(
+        reader.comment_str(),
+        "TI-8x variable writer by Peter Marheine"
+    );
+}
+
+#[test]
+fn verify_accepts_good_checksum() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
+
+    let mut reader = Reader::new(DATA).unwrap();
+    assert!(reader.verify().unwrap());
+    assert_eq!(reader.computed_checksum(), 0x67);
+    reader.finish().unwrap().expect("checksum should be valid");
+}
+
+#[test]
+fn reads_maximum_lengths() {
+    use super::MAX_DATA;
+
+    fn make_file(data_len: u16, data: &[u8]) -> Vec<u8> {
+        let mut out = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net".to_vec();
+        let mut section = vec![0x0d, 0];
+        section.extend_from_slice(&data_len.to_le_bytes());
+        section.extend_from_slice(b"\0A\0\0\0\0\0\0\0\0\0");
+        section.extend_from_slice(&data_len.to_le_bytes());
+        section.extend_from_slice(data);
+        // The section length can't represent more than MAX_DATA bytes of data, so saturate
+        let section_len = data_len.saturating_add(17);
+        out.extend_from_slice(&section_len.to_le_bytes());
+        out.extend_from_slice(&section);
+        let mut checksum = Checksum::new();
+        checksum.update(&section);
+        out.extend_from_slice(&checksum.finalize().to_le_bytes());
+        out
+    }
+
+    // The data section fills its 16-bit length exactly
+    let data = vec![0x5a; MAX_DATA as usize];
+    let file = make_file(MAX_DATA, &data);
+    let mut reader = Reader::new(&file[..]).unwrap();
+    assert_eq!(reader.data_section_len(), u16::MAX);
+    assert_eq!(reader.len(), MAX_DATA);
+    assert_eq!(reader.read_all_data().unwrap(), data);
+    assert_eq!(reader.read(&mut [0]).unwrap(), 0);
+    reader.finish().unwrap().expect("checksum should be valid");
+
+    // One more byte and the section length can't be represented
+    let file = make_file(MAX_DATA + 1, &[0x5a; MAX_DATA as usize + 1]);
+    let e = Reader::new(&file[..]).err().unwrap();
+    assert!(matches!(
+        e.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::Malformed(_))
+    ));
+}
+
+#[test]
+fn rejects_overflowing_lengths() {
+    fn read_error(data: &[u8]) -> ReadError {
+        let e = Reader::new(data)
+            .err()
+            .expect("reader should return an error");
+        *e.into_inner().unwrap().downcast().unwrap()
+    }
+
+    // Data length of 0xffff overflows when the header length is added
+    let data = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                 \xff\xff\x0d\0\xff\xff\x15A\0\0\0\0\0\0\0\0\0\xff\xff\xff\xff";
+    assert!(matches!(read_error(data), ReadError::Malformed(_)));
+
+    // Length prefixes must account for exactly the rest of the data, whether they claim more or
+    // less than there is
+    for (prefix, expected) in [(b"\x01\0", 1), (b"\x04\0", 4)] {
+        let mut data = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                         \x16\0\x0d\0\x05\0\x05A\0\0\0\0\0\0\0\0\0\x05\0"
+            .to_vec();
+        data.extend_from_slice(prefix);
+        data.extend_from_slice(b"\xbb\x6d\xc9\0\0");
+        assert!(matches!(
+            read_error(&data),
+            ReadError::DataLengthMismatch(5, x) if x == expected
+        ));
+    }
+
+    // Inner length of 0xffff overflows when the length of the length is added
+    let data = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                 \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\xff\xff";
+    assert!(matches!(
+        read_error(data),
+        ReadError::DataLengthMismatch(2, 0xffff)
+    ));
+}
+
+/// Types that are recognized but can't appear in variable files are errors, not panics.
+#[test]
+fn rejects_unsupported_types() {
+    for ty in [
+        VariableType::Undefined,
+        VariableType::Unknown,
+        VariableType::LCD,
+    ] {
+        let mut data = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                         \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0"
+            .to_vec();
+        data[59] = ty as u8;
+        let e = Reader::new(&data[..]).err().unwrap();
+        assert!(matches!(
+            e.get_ref().and_then(|e| e.downcast_ref()),
+            Some(&ReadError::UnsupportedType(t)) if t == ty
+        ));
+    }
+}
+
+/// Corrupting any single byte of a file, or truncating it anywhere, must only ever produce
+/// errors. This covers the same ground as the fuzz target in `fuzz/`, deterministically.
+#[test]
+fn corrupt_files_do_not_panic() {
+    // A group containing a real, which exercises member parsing too
+    let group = b"\0\0\0\0\0\0A\0\0\x00\x80\x10\0\0\0\0\0\0";
+    let mut w =
+        crate::Writer::new(Cursor::new(Vec::new()), VariableType::Group, "GRP", true).unwrap();
+    crate::io::Write::write_all(&mut w, group).unwrap();
+    let file = w.close().unwrap().into_inner();
+    assert_eq!(
+        Reader::new(&file[..])
+            .unwrap()
+            .group_members()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    fn exercise(data: &[u8]) {
+        let _ = peek_header(&mut &data[..]);
+        if let Ok(mut reader) = Reader::new(data) {
+            let _ = reader.group_members();
+            let _ = reader.read_to_end(&mut Vec::new());
+            let _ = reader.finish();
+        }
+        if let Ok(mut reader) = Reader::new(Cursor::new(data)) {
+            let _ = reader.seek(SeekFrom::End(-1));
+            let _ = reader.read(&mut [0; 4]);
+        }
+    }
+
+    for len in 0..file.len() {
+        exercise(&file[..len]);
+    }
+    let mut data = file.clone();
+    for i in 0..data.len() {
+        for b in 0..=u8::MAX {
+            data[i] = b;
+            exercise(&data);
+        }
+        data[i] = file[i];
+    }
+}
+
+#[test]
+fn reads_all_data() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\x36\x01";
+
+    let mut reader = Reader::new(DATA).unwrap();
+    assert_eq!(reader.embedded_len(), Some(3));
+    let data = reader.read_all_data().unwrap();
+    assert_eq!(data, b"ABC");
+    assert_eq!(data.capacity(), 3);
+    // Nothing remains
+    assert_eq!(reader.read_all_data().unwrap(), b"");
+    reader.finish().unwrap().expect("checksum should be valid");
+
+    let mut reader = Reader::new(DATA).unwrap();
+    reader.read_exact(&mut [0u8]).unwrap();
+    assert_eq!(reader.read_all_data().unwrap(), b"BC");
+
+    let err = Reader::new(&DATA[..DATA.len() - 3])
+        .unwrap()
+        .read_all_data()
+        .unwrap_err();
+    assert!(matches!(
+        err.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::Truncated {
+            expected: 3,
+            at: "variable data"
+        })
+    ));
+}
+
+#[test]
+fn data_readers_borrow_the_data() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\x36\x01";
+
+    fn read_two<R: Read>(r: R) -> Vec<u8> {
+        let mut out = Vec::new();
+        r.take(2).read_to_end(&mut out).unwrap();
+        out
+    }
+
+    let mut reader = Reader::new(DATA).unwrap();
+    assert_eq!(read_two(reader.data_reader()), b"AB");
+    assert_eq!(read_two(reader.data_reader()), b"C");
+    assert_eq!(read_two(reader.data_reader()), b"");
+    reader.finish().unwrap().expect("checksum should be valid");
+}
+
+#[test]
+fn reads_from_slices() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\x36\x01";
+
+    let (header, data) = Reader::from_slice(DATA).unwrap();
+    assert_eq!(header, peek_header(&mut &DATA[..]).unwrap());
+    assert_eq!(header.ty, VariableType::AppVar);
+    assert_eq!(data, b"ABC");
+    // The data is borrowed from the input
+    assert_eq!(data.as_ptr(), DATA[DATA.len() - 5..].as_ptr());
+
+    let mut trailing = DATA.to_vec();
+    trailing.extend_from_slice(b"next");
+    assert_eq!(Reader::from_slice(&trailing).unwrap().1, b"ABC");
+
+    fn read_error(data: &[u8]) -> ReadError {
+        let e = Reader::from_slice(data).unwrap_err();
+        *e.into_inner().unwrap().downcast().unwrap()
+    }
+    assert!(matches!(
+        read_error(&DATA[..DATA.len() - 1]),
+        ReadError::Truncated {
+            expected: 2,
+            at: "checksum"
+        }
+    ));
+    assert!(matches!(
+        read_error(&DATA[..DATA.len() - 3]),
+        ReadError::Truncated {
+            expected: 3,
+            at: "variable data"
+        }
+    ));
+    let mut corrupt = DATA.to_vec();
+    corrupt[DATA.len() - 3] = b'D';
+    assert!(matches!(
+        read_error(&corrupt),
+        ReadError::InvalidChecksum {
+            computed: 0x137,
+            read: 0x136
+        }
+    ));
+}
+
+#[test]
+fn slices_are_checked_like_streams() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\x36\x01";
+    const HEADER_LEN: usize = 74;
+
+    fn assert_same_error(data: &[u8]) {
+        let stream = Reader::new(data).err().map(|e| format!("{}", e));
+        let slice = Reader::from_slice(data).err().map(|e| format!("{}", e));
+        if stream.is_some() {
+            assert_eq!(slice, stream, "for {:x?}", data);
+        }
+    }
+
+    for len in 0..HEADER_LEN {
+        assert_same_error(&DATA[..len]);
+    }
+    for i in 0..HEADER_LEN {
+        let mut corrupt = DATA.to_vec();
+        corrupt[i] ^= 0x81;
+        assert_same_error(&corrupt);
+    }
+}
+
+#[test]
+fn expects_types() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\x36\x01";
+
+    let mut reader = Reader::new(DATA)
+        .unwrap()
+        .expect_type(VariableType::AppVar)
+        .ok()
+        .unwrap();
+    assert_eq!(reader.read_all_data().unwrap(), b"ABC");
+
+    let (mut reader, actual) = Reader::new(DATA)
+        .unwrap()
+        .expect_type(VariableType::Program)
+        .err()
+        .unwrap();
+    assert_eq!(actual, VariableType::AppVar);
+    // The reader is still usable
+    assert_eq!(reader.read_all_data().unwrap(), b"ABC");
+}
+
+#[test]
+fn into_inner_stops_early() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x14\0\x0d\0\x03\0\x15A\0\0\0\0\0\0\0\0\0\x03\0\x01\0\x42\xac\0";
+
+    let reader = Reader::new(DATA).unwrap();
+    assert_eq!(reader.into_inner(), b"\x42\xac\0");
+
+    // Stops at the stored checksum after reading the data
+    let mut reader = Reader::new(DATA).unwrap();
+    reader.read_to_end(&mut vec![]).unwrap();
+    assert_eq!(reader.into_inner(), b"\xac\0");
+}
+
+#[test]
+fn iterates_concatenated_files() {
+    const FILE: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x14\0\x0d\0\x03\0\x15A\0\0\0\0\0\0\0\0\0\x03\0\x01\0\x42\xac\0";
+    let data = [FILE, FILE].concat();
+
+    let vars = VariableIter::new(&data[..])
+        .collect::<crate::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(vars.len(), 2);
+    for var in vars {
+        assert_eq!(var.name_str(), "A");
+        var.finish().unwrap().expect("checksum should be valid");
+    }
+    assert_eq!(VariableIter::new(&b""[..]).count(), 0);
+
+    // A truncated trailing file is an error, after which iteration stops
+    let mut iter = VariableIter::new(&data[..data.len() - 1]);
+    assert!(iter.next().unwrap().is_ok());
+    let err = iter.next().unwrap().err().unwrap();
+    assert_eq!(err.kind(), crate::io::ErrorKind::UnexpectedEof);
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn strict_reader_checks_names() {
+    fn with_name(ty: u8, name: &[u8; 8]) -> Vec<u8> {
+        let mut data = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                         \x13\0\x0d\0\x02\0"
+            .to_vec();
+        data.push(ty);
+        data.extend_from_slice(name);
+        data.extend_from_slice(b"\0\0\x02\0\0\0\0\0");
+        data
+    }
+
+    assert!(Reader::new_strict(&with_name(0x15, b"A1\x5b\0\0\0\0\0")[..]).is_ok());
+    for name in [b"lower\0\0\0", b"1ABC\0\0\0\0", b"A\0B\0\0\0\0\0"] {
+        let data = with_name(0x15, name);
+        assert!(Reader::new(&data[..]).is_ok());
+        let err = Reader::new_strict(&data[..]).err().unwrap();
+        assert!(matches!(
+            err.into_inner().unwrap().downcast_ref::<ReadError>(),
+            Some(ReadError::InvalidName(n)) if n == name
+        ));
+    }
+    // List names are tokens, so aren't checked
+    let list = with_name(0x01, b"\x5d\x01\0\0\0\0\0\0");
+    assert!(Reader::new_strict(&list[..]).is_ok());
+}
+
+#[test]
+fn peeks_headers() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x14\0\x0d\0\x03\0\x15A\0\0\0\0\0\0\0\0\x80\x03\0\x01\0\x42\x2c\x01";
+
+    let mut input = DATA;
+    let header = peek_header(&mut input).unwrap();
+    assert_eq!(header.ty, VariableType::AppVar);
+    assert_eq!(&header.name, b"A\0\0\0\0\0\0\0");
+    assert!(header.archived);
+    assert_eq!(header.version, 0);
+    assert_eq!(
+        &header.comment,
+        b"Created by SourceCoder 3 - sc.cemetech.net"
+    );
+    assert_eq!(header.data_len, 1);
+    assert_eq!(input, b"\x42\x2c\x01");
+}
+
+#[test]
+fn reports_truncation() {
+    fn truncated_at(data: &[u8]) -> &'static str {
+        let mut reader = match Reader::new(data) {
+            Ok(r) => r,
+            Err(e) => return truncation_field(e),
+        };
+        truncation_field(reader.verify().unwrap_err())
+    }
+
+    fn truncation_field(e: Error) -> &'static str {
+        assert_eq!(e.kind(), crate::io::ErrorKind::UnexpectedEof);
+        match *e.into_inner().unwrap().downcast::<ReadError>().unwrap() {
+            ReadError::Truncated { at, .. } => at,
+            e => panic!("expected truncation error, got {:?}", e),
+        }
+    }
+
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x14\0\x0d\0\x03\0\x15A\0\0\0\0\0\0\0\0\0\x03\0\x01\0\x42\xac\0";
+    assert_eq!(truncated_at(&DATA[..5]), "signature");
+    assert_eq!(truncated_at(&DATA[..50]), "comment");
+    assert_eq!(truncated_at(&DATA[..60]), "variable name");
+    assert_eq!(truncated_at(&DATA[..DATA.len() - 3]), "variable data");
+    assert_eq!(truncated_at(&DATA[..DATA.len() - 1]), "checksum");
+
+    // Other IO errors are passed through
+    struct Broken;
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> crate::io::Result<usize> {
+            Err(crate::io::ErrorKind::InvalidInput.into())
+        }
+    }
+    let e = Reader::new(Broken).err().unwrap();
+    assert_eq!(e.kind(), crate::io::ErrorKind::InvalidInput);
+    assert!(e.into_inner().is_none());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn seeks_within_data() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\x36\x01";
+
+    let mut reader = Reader::new(Cursor::new(DATA)).unwrap();
+    // Seeking to the current position doesn't prevent verification
+    assert_eq!(reader.stream_position().unwrap(), 0);
+    assert!(reader.verify().unwrap());
+
+    let mut buf = [0u8; 2];
+    assert_eq!(reader.seek(SeekFrom::Start(1)).unwrap(), 1);
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"BC");
+    assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 0);
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"AB");
+    assert_eq!(reader.seek(SeekFrom::Current(-1)).unwrap(), 1);
+    let mut rest = vec![];
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"BC");
+
+    assert!(reader.seek(SeekFrom::Start(4)).is_err());
+    assert!(reader.seek(SeekFrom::Current(-4)).is_err());
+    let err = reader.verify().unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<ReadError>(),
+        Some(ReadError::ChecksumUnavailable)
+    ));
+    assert_eq!(reader.into_inner().position() as usize, DATA.len() - 2);
+}
+
+#[test]
+fn finish_error_keeps_unread_data() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\0\0";
+
+    let mut reader = Reader::new(DATA).unwrap();
+    let mut first = [0u8];
+    reader.read_exact(&mut first).unwrap();
+    let err = reader.finish_salvaging().unwrap().unwrap_err();
+    assert_eq!(err.computed_checksum(), 0x136);
+    assert_eq!(err.read_checksum(), 0);
+    assert_eq!(err.unread_data(), b"BC");
+    let (rest, data) = err.into_parts();
+    assert!(rest.is_empty());
+    assert_eq!(data, b"BC");
+
+    // Plain finishing doesn't keep the data
+    let mut reader = Reader::new(DATA).unwrap();
+    reader.read_exact(&mut first).unwrap();
+    let err = reader.finish().unwrap().unwrap_err();
+    assert_eq!(err.computed_checksum(), 0x136);
+    assert!(err.unread_data().is_empty());
+
+    // Nor does salvaging a valid file return anything but the reader
+    let mut valid = DATA.to_vec();
+    valid[DATA.len() - 2..].copy_from_slice(&[0x36, 0x01]);
+    let rest = Reader::new(&valid[..])
+        .unwrap()
+        .finish_salvaging()
+        .unwrap()
+        .unwrap();
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn verify_detects_bad_checksum() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x68\0";
+
+    let mut reader = Reader::new(DATA).unwrap();
+    assert!(!reader.verify().unwrap());
+    // Verifying again gives the same result without reading further
+    assert!(!reader.verify().unwrap());
+
+    let err = reader
+        .finish()
+        .unwrap()
+        .expect_err("checksum should be invalid");
+    assert_eq!(err.computed_checksum, 0x67);
+    assert_eq!(err.read_checksum, 0x68);
+}
+
+#[test]
+fn unverified_readers_ignore_checksums() {
+    // As in verify_detects_bad_checksum, but with trailing data after the file
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x68\0next";
+
+    let mut reader = Reader::new_unverified(DATA).unwrap();
+    assert_eq!(reader.ty(), VariableType::AppVar);
+    assert_eq!(reader.read_all_data().unwrap(), b"");
+    assert_eq!(reader.computed_checksum(), 0);
+    assert!(matches!(
+        reader
+            .verify()
+            .unwrap_err()
+            .get_ref()
+            .and_then(|e| e.downcast_ref()),
+        Some(ReadError::ChecksumUnavailable)
+    ));
+    let rest = reader.finish().unwrap().expect("checksum is not verified");
+    assert_eq!(rest, b"next");
+
+    // Seeking doesn't prevent finishing, since there's no checksum to invalidate
+    let mut reader = Reader::new_unverified(Cursor::new(DATA)).unwrap();
+    reader.seek(SeekFrom::End(0)).unwrap();
+    let rest = reader.finish().unwrap().ok().unwrap();
+    assert_eq!(rest.position(), DATA.len() as u64 - 4);
+
+    // Truncation is still detected
+    let reader = Reader::new_unverified(&DATA[..DATA.len() - 5]).unwrap();
+    assert!(reader.finish().is_err());
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn reads_buffered_lines() {
+    use std::io::BufRead;
+
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x1b\0\x0d\0\x0a\0\x15A\0\0\0\0\0\0\0\0\0\x0a\0\x08\0ab\ncd\nef\xe8\x02";
+
+    let mut reader = Reader::new(std::io::BufReader::with_capacity(4, DATA)).unwrap();
+    let lines = (&mut reader)
+        .lines()
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(lines, ["ab", "cd", "ef"]);
+    assert_eq!(reader.computed_checksum(), 0x2e8);
+    assert!(reader.verify().unwrap());
+}
+
+#[test]
+fn lenient_readers_skip_long_headers() {
+    // An archived program with two bytes of padding after the flags
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x18\0\x0f\0\x05\0\x05A\0\0\0\0\0\0\0\0\x80\xff\xff\x05\0\x03\0\xbb\x6d\xc9\xd1\x04";
+
+    let e = Reader::new(DATA).err().unwrap();
+    assert!(matches!(
+        e.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::UnknownHeaderLength(15))
+    ));
+
+    let mut reader = Reader::new_lenient(DATA).unwrap();
+    assert_eq!(reader.entry_header_len(), 15);
+    assert_eq!(reader.ty(), VariableType::Program);
+    assert_eq!(reader.name_str(), "A");
+    assert!(reader.is_archived());
+    assert_eq!(reader.raw_entry_header(), &DATA[55..74]);
+    assert_eq!(reader.read_all_data().unwrap(), b"\xbb\x6d\xc9");
+    assert!(reader.verify().unwrap());
+
+    // Other unusual lengths are still rejected
+    let mut short = DATA.to_vec();
+    short[55] = 12;
+    assert!(Reader::new_lenient(&short[..]).is_err());
+
+    // Long headers can't overflow the computed section length
+    let mut long = DATA.to_vec();
+    long[55..57].copy_from_slice(b"\xff\xff");
+    let e = Reader::new_lenient(&long[..]).err().unwrap();
+    assert!(matches!(
+        e.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::Malformed(_))
+    ));
+}
+
+#[test]
+fn checks_checksums_without_consuming() {
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x16\0\x0d\0\x05\0\x15A\0\0\0\0\0\0\0\0\0\x05\0\x03\0ABC\x36\x01";
+
+    let mut reader = Reader::new(Cursor::new(DATA)).unwrap();
+    let mut first = [0u8];
+    reader.read_exact(&mut first).unwrap();
+    assert!(reader.checksum_is_valid().unwrap());
+    // Checking again at the end of the data doesn't need to seek
+    assert_eq!(reader.read_all_data().unwrap(), b"BC");
+    assert!(reader.checksum_is_valid().unwrap());
+    assert!(reader.verify().unwrap());
+    let rest = reader.finish().unwrap().ok().unwrap();
+    assert_eq!(rest.position(), DATA.len() as u64);
+
+    let mut corrupt = DATA.to_vec();
+    *corrupt.last_mut().unwrap() = 0;
+    let mut reader = Reader::new(Cursor::new(corrupt)).unwrap();
+    assert!(!reader.checksum_is_valid().unwrap());
+    assert_eq!(reader.read_all_data().unwrap(), b"ABC");
+    assert!(reader.finish().unwrap().is_err());
+}
+
+#[test]
+fn detects_other_models() {
+    fn read_error(data: &[u8]) -> ReadError {
+        let e = Reader::new(data)
+            .err()
+            .expect("reader should return an error");
+        *e.into_inner().unwrap().downcast().unwrap()
+    }
+
+    assert!(matches!(
+        read_error(b"**TI85**\x1a\x0c\0"),
+        ReadError::UnsupportedModel(Model::TI85)
+    ));
+    assert!(matches!(
+        read_error(b"**TI86**\x1a\x0a\0"),
+        ReadError::UnsupportedModel(Model::TI86)
+    ));
+    for (signature, model) in [
+        (b"**TI89**", Model::TI89),
+        (b"**TI92**", Model::TI92),
+        (b"**TI92P*", Model::TI92Plus),
+    ] {
+        let mut data = signature.to_vec();
+        data.extend_from_slice(b"\x01\0main\0\0\0\0");
+        assert!(matches!(
+            read_error(&data),
+            ReadError::UnsupportedModel(m) if m == model
+        ));
+    }
+    assert!(matches!(
+        read_error(b"**TI83F*\x1a\x0a\x01"),
+        ReadError::InvalidSignature(_)
+    ));
+    assert!(matches!(
+        read_error(b"something else"),
+        ReadError::InvalidSignature(_)
+    ));
+    assert!(matches!(
+        read_error(b"**TIFL**\x01\x00\x00"),
+        ReadError::FlashFile
+    ));
+
+    // Files that can be read are always for the TI-83+ family
+    const DATA: &[u8] = b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+                          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0";
+    assert_eq!(Reader::new(DATA).unwrap().model(), Model::TI83Plus);
+}
@@ -0,0 +1,239 @@
+//! Decoding of variables of any type into structured values.
+
+#[cfg(feature = "detokenize")]
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{
+    decode_complex, decode_complex_list, decode_list, decode_matrix, decode_picture, decode_real,
+    decode_table_setup, decode_window, DecodeError, GroupMember, Matrix, Picture, PictureModel,
+    Reader, TableSetup, WindowSettings,
+};
+use crate::io::{Error, Read};
+use crate::VariableType;
+
+/// The data of a variable, decoded according to its type by [`decode_auto`].
+///
+/// Types without a structured decoder are returned as [`Raw`](DecodedVariable::Raw) data, so
+/// every variable can be decoded. As decoders are added for more types their variables will be
+/// returned as new variants instead, so matches on this type must include a wildcard arm.
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum DecodedVariable {
+    /// A [`Real`](VariableType::Real) number.
+    Real(f64),
+    /// A [`Complex`](VariableType::Complex) number, as real and imaginary parts.
+    Complex(f64, f64),
+    /// The elements of a [`List`](VariableType::List).
+    List(Vec<f64>),
+    /// The elements of a [`ComplexList`](VariableType::ComplexList).
+    ComplexList(Vec<(f64, f64)>),
+    /// A [`Matrix`](VariableType::Matrix).
+    Matrix(Matrix),
+    /// The settings in a [`Window`](VariableType::Window) variable.
+    Window(WindowSettings),
+    /// The settings in a [`TableSetup`](VariableType::TableSetup) variable.
+    TableSetup(TableSetup),
+    /// A [`Picture`](VariableType::Picture), in whichever format its size matches.
+    Picture(Picture),
+    /// The members of a [`Group`](VariableType::Group).
+    Group(Vec<GroupMember>),
+    /// The source of a program, as returned by [`detokenize`](super::tokens::detokenize).
+    ///
+    /// This includes protected and temporary programs. Only [`decode_auto_detokenized`] returns
+    /// this and the other text variants.
+    #[cfg(feature = "detokenize")]
+    Program(String),
+    /// The text of a [`String`](VariableType::String), as returned by
+    /// [`decode_string`](super::decode_string).
+    #[cfg(feature = "detokenize")]
+    String(String),
+    /// The text of an equation, as returned by [`decode_equation`](super::decode_equation).
+    ///
+    /// This includes [`NewEquation`](VariableType::NewEquation) variables.
+    #[cfg(feature = "detokenize")]
+    Equation(String),
+    /// The data of a variable of a type that has no structured decoder.
+    Raw(Vec<u8>),
+}
+
+/// Read all of a variable's remaining data and decode it according to its type.
+///
+/// This dispatches to the `decode_*` function for the reader's [`ty`](Reader::ty), so callers
+/// have a single entry point for every type of variable: types that can't be decoded are
+/// returned as [`DecodedVariable::Raw`]. Data should not have been read from the reader yet, and
+/// its checksum can be checked with [`finish`](Reader::finish) afterward.
+///
+/// Programs, strings and equations are always returned as raw tokens, even if the `detokenize`
+/// feature is enabled: features are shared by every crate in a build, so output that depended on
+/// it could change when an unrelated dependency enabled it. [`decode_auto_detokenized`] decodes
+/// them to text instead.
+///
+/// Data that can't be decoded as its type is returned as an error wrapping the [`DecodeError`];
+/// malformed groups return the same errors as [`Reader::group_members`].
+pub fn decode_auto<R: Read>(reader: &mut Reader<R>) -> crate::io::Result<DecodedVariable> {
+    use VariableType::*;
+
+    let ty = reader.ty();
+    if ty == Group {
+        return reader.group_members().map(DecodedVariable::Group);
+    }
+
+    let data = reader.read_all_data()?;
+    let decoded = match ty {
+        Real => decode_real(&data).map(DecodedVariable::Real),
+        Complex => decode_complex(&data).map(|(re, im)| DecodedVariable::Complex(re, im)),
+        List => decode_list(&data).map(DecodedVariable::List),
+        ComplexList => decode_complex_list(&data).map(DecodedVariable::ComplexList),
+        Matrix => decode_matrix(&data).map(DecodedVariable::Matrix),
+        Window => decode_window(&data).map(DecodedVariable::Window),
+        TableSetup => decode_table_setup(&data).map(DecodedVariable::TableSetup),
+        Picture => {
+            let model = if data.len() == PictureModel::Color.size() {
+                PictureModel::Color
+            } else {
+                PictureModel::Monochrome
+            };
+            decode_picture(&data, model).map(DecodedVariable::Picture)
+        }
+        _ => Ok(DecodedVariable::Raw(data)),
+    };
+    decoded.map_err(|e: DecodeError| Error::other(e))
+}
+
+/// Read and decode a variable like [`decode_auto`], but also decode the tokens of programs,
+/// strings and equations to text.
+///
+/// These are returned as [`DecodedVariable::Program`], [`DecodedVariable::String`] and
+/// [`DecodedVariable::Equation`] respectively, and other types as by `decode_auto`.
+#[cfg(feature = "detokenize")]
+pub fn decode_auto_detokenized<R: Read>(
+    reader: &mut Reader<R>,
+) -> crate::io::Result<DecodedVariable> {
+    use VariableType::*;
+
+    let ty = reader.ty();
+    let data = match decode_auto(reader)? {
+        DecodedVariable::Raw(data) => data,
+        decoded => return Ok(decoded),
+    };
+    let decoded = match ty {
+        Program | ProtectedProgram | TemporaryProgram => {
+            super::tokens::detokenize(&data).map(DecodedVariable::Program)
+        }
+        String => Ok(DecodedVariable::String(super::decode_string(&data))),
+        Equation | NewEquation => Ok(DecodedVariable::Equation(super::decode_equation(&data))),
+        _ => Ok(DecodedVariable::Raw(data)),
+    };
+    decoded.map_err(|e: DecodeError| Error::other(e))
+}
+
+#[cfg(test)]
+type TestReader = Reader<crate::io::Cursor<Vec<u8>>>;
+
+#[cfg(test)]
+fn decode_written_with(
+    decode: fn(&mut TestReader) -> crate::io::Result<DecodedVariable>,
+    ty: VariableType,
+    write: impl FnOnce(&mut crate::Writer<crate::io::Cursor<Vec<u8>>>),
+) -> crate::io::Result<DecodedVariable> {
+    let mut w = crate::Writer::new(crate::io::Cursor::new(Vec::new()), ty, "A", false).unwrap();
+    write(&mut w);
+    let file = w.close().unwrap().into_inner();
+    let mut reader = Reader::new(crate::io::Cursor::new(file)).unwrap();
+    let decoded = decode(&mut reader)?;
+    assert!(reader.finish().unwrap().is_ok());
+    Ok(decoded)
+}
+
+#[cfg(test)]
+fn decode_written(
+    ty: VariableType,
+    write: impl FnOnce(&mut crate::Writer<crate::io::Cursor<Vec<u8>>>),
+) -> crate::io::Result<DecodedVariable> {
+    decode_written_with(decode_auto, ty, write)
+}
+
+#[test]
+fn decodes_by_type() {
+    use crate::io::Write;
+
+    assert_eq!(
+        decode_written(VariableType::Real, |w| w.write_real(1.5).unwrap()).unwrap(),
+        DecodedVariable::Real(1.5)
+    );
+    assert_eq!(
+        decode_written(VariableType::List, |w| w.write_list(&[1.0, 2.0]).unwrap()).unwrap(),
+        DecodedVariable::List(vec![1.0, 2.0])
+    );
+    let setup = TableSetup {
+        tbl_start: 1.0,
+        delta_tbl: 2.0,
+        flags: 0,
+    };
+    assert_eq!(
+        decode_written(VariableType::TableSetup, |w| w
+            .write_table_setup(&setup)
+            .unwrap())
+        .unwrap(),
+        DecodedVariable::TableSetup(setup)
+    );
+    let picture = [0u8; 756];
+    assert!(matches!(
+        decode_written(VariableType::Picture, |w| w.write_all(&picture).unwrap()).unwrap(),
+        DecodedVariable::Picture(p) if p.model() == PictureModel::Monochrome
+    ));
+    assert_eq!(
+        decode_written(VariableType::AppVar, |w| w.write_all(b"data").unwrap()).unwrap(),
+        DecodedVariable::Raw(b"data".to_vec())
+    );
+    // Tokens are left alone whether or not detokenizing is available
+    assert_eq!(
+        decode_written(VariableType::Program, |w| w
+            .write_all(b"\xde\x2a\x48\x49\x2a")
+            .unwrap())
+        .unwrap(),
+        DecodedVariable::Raw(b"\xde\x2a\x48\x49\x2a".to_vec())
+    );
+
+    let err = decode_written(VariableType::Real, |w| w.write_all(&[0x0c; 9]).unwrap()).unwrap_err();
+    assert_eq!(
+        err.into_inner().unwrap().downcast_ref::<DecodeError>(),
+        Some(&DecodeError::NotReal(0x0c))
+    );
+}
+
+#[test]
+#[cfg(feature = "detokenize")]
+fn detokenizes_text_types() {
+    use crate::io::Write;
+
+    fn decode(ty: VariableType, data: &[u8]) -> crate::io::Result<DecodedVariable> {
+        decode_written_with(decode_auto_detokenized, ty, |w| w.write_all(data).unwrap())
+    }
+
+    assert_eq!(
+        decode(VariableType::Program, b"\xde\x2a\x48\x49\x2a").unwrap(),
+        DecodedVariable::Program("Disp \"HI\"".into())
+    );
+    assert_eq!(
+        decode(VariableType::String, b"\x48\x49").unwrap(),
+        DecodedVariable::String("HI".into())
+    );
+    assert_eq!(
+        decode(VariableType::Equation, b"\x58\x0d\x70\x32").unwrap(),
+        DecodedVariable::Equation("X²+2".into())
+    );
+    // Other types are decoded as usual
+    assert_eq!(
+        decode(VariableType::AppVar, b"data").unwrap(),
+        DecodedVariable::Raw(b"data".to_vec())
+    );
+    assert!(matches!(
+        decode_written_with(decode_auto_detokenized, VariableType::Real, |w| w
+            .write_real(1.5)
+            .unwrap())
+        .unwrap(),
+        DecodedVariable::Real(x) if x == 1.5
+    ));
+}
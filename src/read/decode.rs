@@ -0,0 +1,943 @@
+//! Decoders for the structured data contained in some variable types.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::io::Read;
+
+/// Errors encountered when decoding variable data.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data was not the length required for the value being decoded.
+    #[error("Expected {expected} bytes of data but got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+    /// The value's flags byte indicates that it isn't a real number.
+    #[error("Value flags {0:#x} do not indicate a real number")]
+    NotReal(u8),
+    /// The value's flags byte indicates that it isn't part of a complex number.
+    #[error("Value flags {0:#x} do not indicate a complex number")]
+    NotComplex(u8),
+    /// A mantissa byte contained a nibble that isn't a decimal digit.
+    #[error("Mantissa byte {0:#x} is not valid BCD")]
+    InvalidDigit(u8),
+    /// Tokenized data ended with the first byte of a two-byte token.
+    #[error("Data ends in the middle of a two-byte token beginning with {0:#x}")]
+    TruncatedToken(u8),
+}
+
+/// The size of a TI real number, in bytes.
+pub const REAL_SIZE: usize = 9;
+
+/// The size of a TI complex number, in bytes.
+pub const COMPLEX_SIZE: usize = 2 * REAL_SIZE;
+
+/// The object type in the flags byte of both halves of a complex number.
+pub(crate) const COMPLEX_FLAG: u8 = 0x0c;
+
+/// Decode a real number from the 9-byte TI floating-point format.
+///
+/// The first byte contains flags: bit 7 is set for negative numbers and the low 5 bits are an
+/// object type, which must be zero for real numbers (complex values and undefined values have
+/// nonzero types and are rejected with [`DecodeError::NotReal`]). The second byte is the decimal
+/// exponent biased by 0x80, and the remaining 7 bytes are 14 packed BCD mantissa digits with an
+/// implied decimal point after the first digit.
+pub fn decode_real(bytes: &[u8]) -> Result<f64, DecodeError> {
+    if bytes.len() != REAL_SIZE {
+        return Err(DecodeError::InvalidLength {
+            expected: REAL_SIZE,
+            actual: bytes.len(),
+        });
+    }
+
+    let flags = bytes[0];
+    if flags & 0x1f != 0 {
+        return Err(DecodeError::NotReal(flags));
+    }
+    parse_number(bytes)
+}
+
+/// Decode the sign, exponent and mantissa of a number, ignoring its object type.
+fn parse_number(bytes: &[u8]) -> Result<f64, DecodeError> {
+    let flags = bytes[0];
+    let exponent = bytes[1] as i32 - 0x80;
+
+    // Formatting the digits and parsing them back gets the standard library to do correctly-rounded
+    // decimal to binary conversion for us.
+    let mut s = String::with_capacity(24);
+    if flags & 0x80 != 0 {
+        s.push('-');
+    }
+    for (i, &b) in bytes[2..].iter().enumerate() {
+        let (hi, lo) = (b >> 4, b & 0xf);
+        if hi > 9 || lo > 9 {
+            return Err(DecodeError::InvalidDigit(b));
+        }
+        s.push((b'0' + hi) as char);
+        if i == 0 {
+            s.push('.');
+        }
+        s.push((b'0' + lo) as char);
+    }
+    s.push('e');
+    s.push_str(&exponent.to_string());
+
+    Ok(s.parse()
+        .expect("formatted real should always be a valid float"))
+}
+
+/// Decode a list of real numbers.
+///
+/// List data consists of a 16-bit element count followed by that many reals, each in the format
+/// accepted by [`decode_real`].
+pub fn decode_list(bytes: &[u8]) -> Result<Vec<f64>, DecodeError> {
+    let count = match bytes {
+        [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]) as usize,
+        _ => {
+            return Err(DecodeError::InvalidLength {
+                expected: 2,
+                actual: bytes.len(),
+            })
+        }
+    };
+    let expected = 2 + count * REAL_SIZE;
+    if bytes.len() != expected {
+        return Err(DecodeError::InvalidLength {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    bytes[2..]
+        .chunks_exact(REAL_SIZE)
+        .map(decode_real)
+        .collect()
+}
+
+/// Decode a complex number from the 18-byte TI format, returning its real and imaginary parts.
+///
+/// A complex number is two numbers in the format accepted by [`decode_real`], the real part
+/// followed by the imaginary part, except that the object type in each of their flags bytes is
+/// 0x0C rather than zero. Values without that type are rejected with
+/// [`DecodeError::NotComplex`].
+pub fn decode_complex(bytes: &[u8]) -> Result<(f64, f64), DecodeError> {
+    if bytes.len() != COMPLEX_SIZE {
+        return Err(DecodeError::InvalidLength {
+            expected: COMPLEX_SIZE,
+            actual: bytes.len(),
+        });
+    }
+
+    let (re, im) = bytes.split_at(REAL_SIZE);
+    for part in [re, im] {
+        if part[0] & 0x1f != COMPLEX_FLAG {
+            return Err(DecodeError::NotComplex(part[0]));
+        }
+    }
+    Ok((parse_number(re)?, parse_number(im)?))
+}
+
+/// Decode a list of complex numbers.
+///
+/// Complex list data consists of a 16-bit element count followed by that many complex numbers,
+/// each in the format accepted by [`decode_complex`].
+pub fn decode_complex_list(bytes: &[u8]) -> Result<Vec<(f64, f64)>, DecodeError> {
+    let count = match bytes {
+        [lo, hi, ..] => u16::from_le_bytes([*lo, *hi]) as usize,
+        _ => {
+            return Err(DecodeError::InvalidLength {
+                expected: 2,
+                actual: bytes.len(),
+            })
+        }
+    };
+    let expected = 2 + count * COMPLEX_SIZE;
+    if bytes.len() != expected {
+        return Err(DecodeError::InvalidLength {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    bytes[2..]
+        .chunks_exact(COMPLEX_SIZE)
+        .map(decode_complex)
+        .collect()
+}
+
+/// Decodes real numbers one at a time from list or matrix data.
+///
+/// This reads the data lazily, so elements can be processed without holding the whole list or
+/// matrix in memory. It's usually constructed with a [`Reader`](crate::Reader) as the input:
+///
+/// ```
+/// # use tifiles::{Reader, read::RealIter};
+/// # fn doit<R: tifiles::io::Read>(input: R) -> tifiles::io::Result<()> {
+/// let reader = Reader::new(input)?;
+/// let sum = RealIter::list(reader)?.sum::<tifiles::io::Result<f64>>()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Iteration stops after the number of elements given by the data's header, or after the first
+/// error.
+#[derive(Debug)]
+pub struct RealIter<R> {
+    r: R,
+    rows: usize,
+    cols: usize,
+    remaining: usize,
+}
+
+impl<R: Read> RealIter<R> {
+    /// Iterate over the elements of a list, as laid out for [`decode_list`].
+    pub fn list(mut r: R) -> crate::io::Result<Self> {
+        let mut count = [0u8; 2];
+        r.read_exact(&mut count)?;
+        let count = u16::from_le_bytes(count) as usize;
+        Ok(RealIter {
+            r,
+            rows: count,
+            cols: 1,
+            remaining: count,
+        })
+    }
+
+    /// Iterate over the elements of a matrix in row-major order, as laid out for
+    /// [`decode_matrix`].
+    pub fn matrix(mut r: R) -> crate::io::Result<Self> {
+        let mut dims = [0u8; 2];
+        r.read_exact(&mut dims)?;
+        let (cols, rows) = (dims[0] as usize, dims[1] as usize);
+        Ok(RealIter {
+            r,
+            rows,
+            cols,
+            remaining: rows * cols,
+        })
+    }
+
+    /// Get the dimensions of the data as rows and columns. Lists have a single column.
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Consume the iterator, returning the input.
+    pub fn into_inner(self) -> R {
+        self.r
+    }
+}
+
+impl<R: Read> Iterator for RealIter<R> {
+    type Item = crate::io::Result<f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let mut buf = [0u8; REAL_SIZE];
+        let result = match self.r.read_exact(&mut buf) {
+            Ok(()) => decode_real(&buf).map_err(crate::io::Error::other),
+            Err(e) => Err(e),
+        };
+        self.remaining = if result.is_ok() {
+            self.remaining - 1
+        } else {
+            0
+        };
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+/// A matrix of real numbers.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    /// Elements in row-major order
+    elements: Vec<f64>,
+}
+
+impl Matrix {
+    /// Get the number of rows in the matrix.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Get the number of columns in the matrix.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Get the element at the given row and column, or `None` if out of bounds.
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        Some(self.elements[row * self.cols + col])
+    }
+
+    /// Convert the matrix to a vector of rows.
+    pub fn to_rows(&self) -> Vec<Vec<f64>> {
+        // chunks_exact panics on zero-size chunks, which a matrix with no columns would need
+        if self.cols == 0 {
+            return vec![vec![]; self.rows];
+        }
+        self.elements
+            .chunks_exact(self.cols)
+            .map(<[f64]>::to_vec)
+            .collect()
+    }
+}
+
+/// Decode a matrix of real numbers.
+///
+/// Matrix data consists of a column count byte and a row count byte, followed by the elements in
+/// row-major order, each in the format accepted by [`decode_real`].
+pub fn decode_matrix(bytes: &[u8]) -> Result<Matrix, DecodeError> {
+    let (cols, rows) = match bytes {
+        [cols, rows, ..] => (*cols as usize, *rows as usize),
+        _ => {
+            return Err(DecodeError::InvalidLength {
+                expected: 2,
+                actual: bytes.len(),
+            })
+        }
+    };
+    let expected = 2 + rows * cols * REAL_SIZE;
+    if bytes.len() != expected {
+        return Err(DecodeError::InvalidLength {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    Ok(Matrix {
+        rows,
+        cols,
+        elements: bytes[2..]
+            .chunks_exact(REAL_SIZE)
+            .map(decode_real)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// The number of real values in [`WindowSettings`].
+const WINDOW_VALUES: usize = 23;
+
+/// The size of the data in a [`Window`](crate::VariableType::Window) variable, in bytes.
+pub const WINDOW_SIZE: usize = 1 + WINDOW_VALUES * REAL_SIZE;
+
+/// Graph window settings, as stored in [`Window`](crate::VariableType::Window) variables.
+///
+/// A single variable holds the settings for every graphing mode: the X and Y ranges apply to all
+/// of them, while the θ, T and sequence (n, u, v and w) settings are only used in polar,
+/// parametric and sequence modes respectively. Fields are in the order they are stored in.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WindowSettings {
+    /// The first byte of the data, whose meaning is unknown. It is preserved so that settings can
+    /// be written back unchanged.
+    pub leading_byte: u8,
+    /// Xmin
+    pub x_min: f64,
+    /// Xmax
+    pub x_max: f64,
+    /// Xscl
+    pub x_scl: f64,
+    /// Ymin
+    pub y_min: f64,
+    /// Ymax
+    pub y_max: f64,
+    /// Yscl
+    pub y_scl: f64,
+    /// θmin
+    pub theta_min: f64,
+    /// θmax
+    pub theta_max: f64,
+    /// θstep
+    pub theta_step: f64,
+    /// Tmin
+    pub t_min: f64,
+    /// Tmax
+    pub t_max: f64,
+    /// Tstep
+    pub t_step: f64,
+    /// PlotStart
+    pub plot_start: f64,
+    /// nMax
+    pub n_max: f64,
+    /// u(nMin)
+    pub u_n_min: f64,
+    /// v(nMin)
+    pub v_n_min: f64,
+    /// nMin
+    pub n_min: f64,
+    /// u(nMin-1)
+    pub u_n_min_1: f64,
+    /// v(nMin-1)
+    pub v_n_min_1: f64,
+    /// w(nMin)
+    pub w_n_min: f64,
+    /// PlotStep
+    pub plot_step: f64,
+    /// Xres
+    pub x_res: f64,
+    /// w(nMin-1)
+    pub w_n_min_1: f64,
+}
+
+impl WindowSettings {
+    /// Return the values of the settings in the order they are stored in.
+    pub(crate) fn values(&self) -> [f64; WINDOW_VALUES] {
+        [
+            self.x_min,
+            self.x_max,
+            self.x_scl,
+            self.y_min,
+            self.y_max,
+            self.y_scl,
+            self.theta_min,
+            self.theta_max,
+            self.theta_step,
+            self.t_min,
+            self.t_max,
+            self.t_step,
+            self.plot_start,
+            self.n_max,
+            self.u_n_min,
+            self.v_n_min,
+            self.n_min,
+            self.u_n_min_1,
+            self.v_n_min_1,
+            self.w_n_min,
+            self.plot_step,
+            self.x_res,
+            self.w_n_min_1,
+        ]
+    }
+}
+
+/// Decode graph window settings.
+///
+/// Window data consists of a byte of unknown meaning followed by 23 reals, each in the format
+/// accepted by [`decode_real`], which are the fields of [`WindowSettings`] in order.
+pub fn decode_window(bytes: &[u8]) -> Result<WindowSettings, DecodeError> {
+    if bytes.len() != WINDOW_SIZE {
+        return Err(DecodeError::InvalidLength {
+            expected: WINDOW_SIZE,
+            actual: bytes.len(),
+        });
+    }
+
+    let mut values = [0.0; WINDOW_VALUES];
+    for (value, bytes) in values.iter_mut().zip(bytes[1..].chunks_exact(REAL_SIZE)) {
+        *value = decode_real(bytes)?;
+    }
+    Ok(WindowSettings {
+        leading_byte: bytes[0],
+        x_min: values[0],
+        x_max: values[1],
+        x_scl: values[2],
+        y_min: values[3],
+        y_max: values[4],
+        y_scl: values[5],
+        theta_min: values[6],
+        theta_max: values[7],
+        theta_step: values[8],
+        t_min: values[9],
+        t_max: values[10],
+        t_step: values[11],
+        plot_start: values[12],
+        n_max: values[13],
+        u_n_min: values[14],
+        v_n_min: values[15],
+        n_min: values[16],
+        u_n_min_1: values[17],
+        v_n_min_1: values[18],
+        w_n_min: values[19],
+        plot_step: values[20],
+        x_res: values[21],
+        w_n_min_1: values[22],
+    })
+}
+
+/// The size of the data in a [`TableSetup`](crate::VariableType::TableSetup) variable, in bytes.
+pub const TABLE_SETUP_SIZE: usize = 2 * REAL_SIZE + 1;
+
+/// Table settings, as stored in [`TableSetup`](crate::VariableType::TableSetup) variables.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TableSetup {
+    /// TblStart, the first value of the independent variable shown in the table.
+    pub tbl_start: f64,
+    /// ΔTbl, the step between successive values of the independent variable.
+    pub delta_tbl: f64,
+    /// The flags byte holding the table modes (whether the independent and dependent variables
+    /// are generated automatically or asked for). It is preserved as stored so that settings can
+    /// be written back unchanged.
+    pub flags: u8,
+}
+
+/// Decode table settings.
+///
+/// Table setup data consists of TblStart and ΔTbl, each in the format accepted by
+/// [`decode_real`], followed by a flags byte holding the table modes: 19 bytes in total. Like
+/// other settings variables, the data is preceded by a length prefix in the file, which
+/// [`Reader`](crate::read::Reader) does not include in the data it reads.
+pub fn decode_table_setup(bytes: &[u8]) -> Result<TableSetup, DecodeError> {
+    if bytes.len() != TABLE_SETUP_SIZE {
+        return Err(DecodeError::InvalidLength {
+            expected: TABLE_SETUP_SIZE,
+            actual: bytes.len(),
+        });
+    }
+
+    Ok(TableSetup {
+        tbl_start: decode_real(&bytes[..REAL_SIZE])?,
+        delta_tbl: decode_real(&bytes[REAL_SIZE..2 * REAL_SIZE])?,
+        flags: bytes[2 * REAL_SIZE],
+    })
+}
+
+/// Picture formats used by different calculator models.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PictureModel {
+    /// The monochrome format of the TI-83 Plus and TI-84 Plus.
+    ///
+    /// Pictures are 95 pixels wide and 63 tall, in 756 bytes: each row of pixels is packed into
+    /// 12 bytes from top to bottom, most significant bit first with set bits for dark pixels. The
+    /// final bit of each row is unused, since the rightmost column of the screen can't be drawn on.
+    Monochrome,
+    /// The color format of the TI-84 Plus CE.
+    ///
+    /// Pictures are 266 pixels wide and 165 tall, in 21945 bytes. With 16-bit color they would be
+    /// too large to fit in a variable, so pixels are instead 4-bit indices into the 15 colors
+    /// available to TI-BASIC programs: each row of pixels is packed into 133 bytes from top to
+    /// bottom, with the left pixel of each pair in the high nibble. Index 0 is transparent and
+    /// indices 1 to 15 correspond to color numbers 10 (`BLUE`) through 24 (`DARKGRAY`).
+    Color,
+}
+
+impl PictureModel {
+    fn width(self) -> usize {
+        match self {
+            PictureModel::Monochrome => 95,
+            PictureModel::Color => 266,
+        }
+    }
+
+    fn height(self) -> usize {
+        match self {
+            PictureModel::Monochrome => 63,
+            PictureModel::Color => 165,
+        }
+    }
+
+    /// Bytes of data in each row.
+    fn stride(self) -> usize {
+        match self {
+            PictureModel::Monochrome => 12,
+            PictureModel::Color => 133,
+        }
+    }
+
+    /// Encoded size of a picture.
+    pub(super) fn size(self) -> usize {
+        self.stride() * self.height()
+    }
+}
+
+/// RGB565 values of the colors available to TI-BASIC on the TI-84 Plus CE, from `BLUE` to
+/// `DARKGRAY`.
+const PALETTE: [u16; 15] = [
+    0x001f, 0xf800, 0x0000, 0xf81f, 0x04e0, 0xfc64, 0xb100, 0x0010, 0x049f, 0xffe0, 0xffff, 0xe71c,
+    0xbdd7, 0x8c51, 0x52aa,
+];
+
+/// Expand an RGB565 color to 8 bits per channel.
+fn rgb565_to_rgb8(color: u16) -> [u8; 3] {
+    let r = (color >> 11) & 0x1f;
+    let g = (color >> 5) & 0x3f;
+    let b = color & 0x1f;
+    // Replicate high bits into the low bits so the full range is covered
+    [
+        ((r << 3) | (r >> 2)) as u8,
+        ((g << 2) | (g >> 4)) as u8,
+        ((b << 3) | (b >> 2)) as u8,
+    ]
+}
+
+/// A picture, as stored in [`Picture`](crate::VariableType::Picture) variables.
+///
+/// The layout of the data depends on the [`PictureModel`] it was decoded as.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Picture {
+    model: PictureModel,
+    /// Packed rows of pixels
+    data: Vec<u8>,
+}
+
+impl Picture {
+    /// Get the format of the picture.
+    pub fn model(&self) -> PictureModel {
+        self.model
+    }
+
+    /// Get the width of the picture, in pixels.
+    pub fn width(&self) -> usize {
+        self.model.width()
+    }
+
+    /// Get the height of the picture, in pixels.
+    pub fn height(&self) -> usize {
+        self.model.height()
+    }
+
+    /// Return the raw value of the pixel at the given position, or `None` if out of bounds.
+    ///
+    /// For monochrome pictures this is 1 for dark pixels and 0 for light ones. For color pictures
+    /// it is the palette index described for [`PictureModel::Color`]. Position `(0, 0)` is at the
+    /// top left.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<u8> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let row = &self.data[y * self.model.stride()..];
+        Some(match self.model {
+            PictureModel::Monochrome => (row[x / 8] >> (7 - x % 8)) & 1,
+            PictureModel::Color => (row[x / 2] >> if x.is_multiple_of(2) { 4 } else { 0 }) & 0xf,
+        })
+    }
+
+    /// Return whether the pixel at the given position is set, or `None` if out of bounds.
+    ///
+    /// Pixels are set if they are dark in a monochrome picture, or not transparent in a color
+    /// picture.
+    pub fn get(&self, x: usize, y: usize) -> Option<bool> {
+        self.pixel(x, y).map(|p| p != 0)
+    }
+
+    /// Return the color of the pixel at the given position, or `None` if out of bounds.
+    ///
+    /// Unset pixels (including transparent pixels in color pictures) are white, and dark pixels
+    /// in monochrome pictures are black.
+    pub fn rgb(&self, x: usize, y: usize) -> Option<[u8; 3]> {
+        let pixel = self.pixel(x, y)?;
+        Some(match (self.model, pixel) {
+            (_, 0) => [255, 255, 255],
+            (PictureModel::Monochrome, _) => [0, 0, 0],
+            (PictureModel::Color, i) => rgb565_to_rgb8(PALETTE[i as usize - 1]),
+        })
+    }
+
+    /// Convert the picture to 8-bit RGB pixels in row-major order, with the colors returned by
+    /// [`rgb`](Picture::rgb).
+    pub fn to_rgb8(&self) -> Vec<u8> {
+        self.coordinates()
+            .flat_map(|(x, y)| self.rgb(x, y).unwrap())
+            .collect()
+    }
+
+    /// Convert the picture to 8-bit grayscale pixels in row-major order, with set pixels black
+    /// (0) and unset pixels white (255).
+    pub fn to_luma8(&self) -> Vec<u8> {
+        self.coordinates()
+            .map(|(x, y)| if self.get(x, y) == Some(true) { 0 } else { 255 })
+            .collect()
+    }
+
+    /// Iterate over the coordinates of every pixel, in row-major order.
+    fn coordinates(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width();
+        (0..self.height()).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+}
+
+/// Decode a picture in the format used by the given model.
+///
+/// Picture data must be exactly the size described for the [`PictureModel`]. Decoding a picture
+/// as the wrong model doesn't produce a meaningful image, but pictures of each model are
+/// different sizes so this will return [`DecodeError::InvalidLength`] instead.
+pub fn decode_picture(bytes: &[u8], model: PictureModel) -> Result<Picture, DecodeError> {
+    if bytes.len() != model.size() {
+        return Err(DecodeError::InvalidLength {
+            expected: model.size(),
+            actual: bytes.len(),
+        });
+    }
+
+    Ok(Picture {
+        model,
+        data: bytes.to_vec(),
+    })
+}
+
+#[test]
+#[allow(clippy::approx_constant)]
+fn decodes_reals() {
+    assert_eq!(decode_real(b"\x00\x80\0\0\0\0\0\0\0"), Ok(0.0));
+    assert_eq!(decode_real(b"\x00\x80\x31\x40\0\0\0\0\0"), Ok(3.14));
+    assert_eq!(decode_real(b"\x80\x82\x12\x50\0\0\0\0\0"), Ok(-125.0));
+    assert_eq!(decode_real(b"\x00\x7d\x25\0\0\0\0\0\0"), Ok(0.0025));
+    assert_eq!(
+        decode_real(b"\x00\xe3\x99\x99\x99\x99\x99\x99\x99"),
+        Ok(9.9999999999999e99)
+    );
+    assert_eq!(
+        decode_real(b"\x80\xe3\x99\x99\x99\x99\x99\x99\x99"),
+        Ok(-9.9999999999999e99)
+    );
+}
+
+#[test]
+fn rejects_invalid_reals() {
+    assert_eq!(
+        decode_real(b"\x00\x80\0"),
+        Err(DecodeError::InvalidLength {
+            expected: 9,
+            actual: 3
+        })
+    );
+    // Complex flag
+    assert_eq!(
+        decode_real(b"\x0c\x80\x10\0\0\0\0\0\0"),
+        Err(DecodeError::NotReal(0x0c))
+    );
+    assert_eq!(
+        decode_real(b"\x00\x80\x1a\0\0\0\0\0\0"),
+        Err(DecodeError::InvalidDigit(0x1a))
+    );
+}
+
+#[test]
+fn decodes_complex_numbers() {
+    assert_eq!(
+        decode_complex(b"\x0c\x80\x15\0\0\0\0\0\0\x8c\x7f\x25\0\0\0\0\0\0"),
+        Ok((1.5, -0.25))
+    );
+    assert_eq!(
+        decode_complex_list(b"\x01\0\x0c\x80\0\0\0\0\0\0\0\x0c\x80\x10\0\0\0\0\0\0"),
+        Ok(vec![(0.0, 1.0)])
+    );
+
+    // A pair of reals isn't a complex number
+    assert_eq!(
+        decode_complex(b"\x00\x80\x15\0\0\0\0\0\0\x0c\x80\x25\0\0\0\0\0\0"),
+        Err(DecodeError::NotComplex(0))
+    );
+    assert_eq!(
+        decode_complex(b"\x0c\x80\x15\0\0\0\0\0\0"),
+        Err(DecodeError::InvalidLength {
+            expected: 18,
+            actual: 9
+        })
+    );
+    assert_eq!(
+        decode_complex_list(b"\x02\0\x0c\x80\0\0\0\0\0\0\0\x0c\x80\x10\0\0\0\0\0\0"),
+        Err(DecodeError::InvalidLength {
+            expected: 38,
+            actual: 20
+        })
+    );
+}
+
+#[test]
+fn decodes_lists() {
+    assert_eq!(decode_list(b"\0\0"), Ok(vec![]));
+    assert_eq!(
+        decode_list(b"\x01\0\x00\x80\x10\0\0\0\0\0\0"),
+        Ok(vec![1.0])
+    );
+    assert_eq!(
+        decode_list(
+            b"\x02\0\
+              \x80\x81\x42\0\0\0\0\0\0\
+              \x00\x7f\x50\0\0\0\0\0\0"
+        ),
+        Ok(vec![-42.0, 0.5])
+    );
+
+    assert_eq!(
+        decode_list(b"\x02\0\x00\x80\x10\0\0\0\0\0\0"),
+        Err(DecodeError::InvalidLength {
+            expected: 20,
+            actual: 11
+        })
+    );
+    assert_eq!(
+        decode_list(b"\x01"),
+        Err(DecodeError::InvalidLength {
+            expected: 2,
+            actual: 1
+        })
+    );
+}
+
+#[test]
+fn decodes_matrices() {
+    let empty = decode_matrix(b"\0\0").unwrap();
+    assert_eq!((empty.rows(), empty.cols()), (0, 0));
+    assert_eq!(empty.get(0, 0), None);
+    assert!(empty.to_rows().is_empty());
+
+    // 3 columns, 2 rows
+    let mut data = vec![3, 2];
+    for x in 1..=6 {
+        data.extend_from_slice(&[0, 0x80, x << 4, 0, 0, 0, 0, 0, 0]);
+    }
+    let m = decode_matrix(&data).unwrap();
+    assert_eq!((m.rows(), m.cols()), (2, 3));
+    assert_eq!(m.get(0, 2), Some(3.0));
+    assert_eq!(m.get(1, 0), Some(4.0));
+    assert_eq!(m.get(2, 0), None);
+    assert_eq!(m.to_rows(), vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+
+    assert_eq!(
+        decode_matrix(&data[..data.len() - 9]),
+        Err(DecodeError::InvalidLength {
+            expected: 56,
+            actual: 47
+        })
+    );
+}
+
+#[test]
+fn decodes_windows() {
+    // ZStandard settings
+    let mut data = vec![0u8];
+    for value in [
+        b"\x80\x81\x10", // -10
+        b"\x00\x81\x10", // 10
+        b"\x00\x80\x10", // 1
+    ] {
+        let mut real = [0u8; REAL_SIZE];
+        real[..3].copy_from_slice(value);
+        data.extend_from_slice(&real);
+    }
+    data.extend_from_within(1..);
+    data.resize(WINDOW_SIZE, 0);
+    for real in data[1 + 6 * REAL_SIZE..].chunks_exact_mut(REAL_SIZE) {
+        real[1] = 0x80;
+    }
+
+    let window = decode_window(&data).unwrap();
+    assert_eq!(
+        (window.x_min, window.x_max, window.x_scl),
+        (-10.0, 10.0, 1.0)
+    );
+    assert_eq!(
+        (window.y_min, window.y_max, window.y_scl),
+        (-10.0, 10.0, 1.0)
+    );
+    assert_eq!(window.w_n_min_1, 0.0);
+    assert_eq!(window.values()[..3], [-10.0, 10.0, 1.0]);
+
+    assert_eq!(
+        decode_window(&data[1..]),
+        Err(DecodeError::InvalidLength {
+            expected: WINDOW_SIZE,
+            actual: WINDOW_SIZE - 1
+        })
+    );
+    data[1] |= 0x0c;
+    assert_eq!(decode_window(&data), Err(DecodeError::NotReal(0x8c)));
+}
+
+#[test]
+fn decodes_table_setups() {
+    let data = b"\x80\x81\x10\0\0\0\0\0\0\x00\x7f\x50\0\0\0\0\0\0\x01";
+    assert_eq!(
+        decode_table_setup(data),
+        Ok(TableSetup {
+            tbl_start: -10.0,
+            delta_tbl: 0.5,
+            flags: 1,
+        })
+    );
+    assert_eq!(
+        decode_table_setup(&data[..18]),
+        Err(DecodeError::InvalidLength {
+            expected: TABLE_SETUP_SIZE,
+            actual: 18
+        })
+    );
+}
+
+#[test]
+fn decodes_pictures() {
+    let mut data = vec![0u8; 756];
+    // Top left, and the rightmost pixel of the bottom row
+    data[0] = 0x80;
+    data[755] = 0x02;
+    let picture = decode_picture(&data, PictureModel::Monochrome).unwrap();
+    assert_eq!((picture.width(), picture.height()), (95, 63));
+    assert_eq!(picture.get(0, 0), Some(true));
+    assert_eq!(picture.get(1, 0), Some(false));
+    assert_eq!(picture.get(94, 62), Some(true));
+    assert_eq!(picture.get(95, 62), None);
+    assert_eq!(picture.rgb(0, 0), Some([0, 0, 0]));
+
+    let luma = picture.to_luma8();
+    assert_eq!(luma.len(), 95 * 63);
+    assert_eq!((luma[0], luma[1], luma[luma.len() - 1]), (0, 255, 0));
+
+    assert_eq!(
+        decode_picture(&data[1..], PictureModel::Monochrome),
+        Err(DecodeError::InvalidLength {
+            expected: 756,
+            actual: 755
+        })
+    );
+}
+
+#[test]
+fn decodes_color_pictures() {
+    let mut data = vec![0u8; 21945];
+    // BLUE then RED at the top left, and WHITE at the bottom right
+    data[0] = 0x12;
+    data[21944] = 0x0b;
+    let picture = decode_picture(&data, PictureModel::Color).unwrap();
+    assert_eq!((picture.width(), picture.height()), (266, 165));
+    assert_eq!(picture.pixel(0, 0), Some(1));
+    assert_eq!(picture.rgb(0, 0), Some([0, 0, 255]));
+    assert_eq!(picture.rgb(1, 0), Some([255, 0, 0]));
+    assert_eq!(picture.rgb(2, 0), Some([255, 255, 255]));
+    assert_eq!(picture.get(2, 0), Some(false));
+    assert_eq!(picture.pixel(265, 164), Some(11));
+    assert_eq!(picture.rgb(266, 0), None);
+
+    let rgb = picture.to_rgb8();
+    assert_eq!(rgb.len(), 266 * 165 * 3);
+    assert_eq!(&rgb[..6], &[0, 0, 255, 255, 0, 0]);
+
+    // Monochrome data is a different size
+    assert!(decode_picture(&data[..756], PictureModel::Color).is_err());
+}
+
+#[test]
+fn iterates_reals() {
+    let list = b"\x02\x00\x00\x80\x10\0\0\0\0\0\0\x80\x80\x20\0\0\0\0\0\0trailing";
+    let mut iter = RealIter::list(&list[..]).unwrap();
+    assert_eq!(iter.dimensions(), (2, 1));
+    assert_eq!(iter.next().unwrap().unwrap(), 1.0);
+    assert_eq!(iter.next().unwrap().unwrap(), -2.0);
+    assert!(iter.next().is_none());
+    assert_eq!(iter.into_inner(), b"trailing");
+
+    let matrix = b"\x02\x01\x00\x80\x10\0\0\0\0\0\0\x00\x80\x30\0\0\0\0\0\0";
+    let iter = RealIter::matrix(&matrix[..]).unwrap();
+    assert_eq!(iter.dimensions(), (1, 2));
+    assert_eq!(
+        iter.collect::<crate::io::Result<Vec<_>>>().unwrap(),
+        [1.0, 3.0]
+    );
+
+    // Errors end iteration
+    let mut iter = RealIter::list(&list[..12]).unwrap();
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+}
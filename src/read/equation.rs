@@ -0,0 +1,90 @@
+//! Identification of equation variables.
+
+use core::fmt;
+
+/// The first byte of every equation variable's name, the `tVarEqu` token.
+const EQUATION_NAME_PREFIX: u8 = 0x5e;
+
+/// The slot an [`Equation`](crate::VariableType::Equation) variable occupies, such as `Y1`.
+///
+/// Equations aren't named with text like most variables: instead the name is the two-byte token
+/// for the equation variable, which this decodes. The [`Display`](fmt::Display) impl writes the
+/// name as it appears on a calculator, like `Y1`, `X2T` or `r3`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EquationSlot {
+    /// A function `Y1` through `Y9` or `Y0`, containing the digit in its name.
+    Function(u8),
+    /// The X component of a parametric equation `X1T` through `X6T`.
+    ParametricX(u8),
+    /// The Y component of a parametric equation `Y1T` through `Y6T`.
+    ParametricY(u8),
+    /// A polar equation `r1` through `r6`.
+    Polar(u8),
+    /// The sequence equation `u`.
+    SequenceU,
+    /// The sequence equation `v`.
+    SequenceV,
+    /// The sequence equation `w`.
+    SequenceW,
+}
+
+impl EquationSlot {
+    /// Return the slot identified by a variable name, or `None` if it isn't an equation name.
+    ///
+    /// Only the first two bytes of the name are significant.
+    pub fn from_name(name: &[u8; 8]) -> Option<EquationSlot> {
+        use EquationSlot::*;
+
+        if name[0] != EQUATION_NAME_PREFIX {
+            return None;
+        }
+        Some(match name[1] {
+            // Y0 comes after Y9, as on the keypad
+            b @ 0x10..=0x19 => Function((b - 0x10 + 1) % 10),
+            // X and Y components are interleaved
+            b @ 0x20..=0x2b if b % 2 == 0 => ParametricX((b - 0x20) / 2 + 1),
+            b @ 0x20..=0x2b => ParametricY((b - 0x21) / 2 + 1),
+            b @ 0x40..=0x45 => Polar(b - 0x40 + 1),
+            0x80 => SequenceU,
+            0x81 => SequenceV,
+            0x82 => SequenceW,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for EquationSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use EquationSlot::*;
+        match self {
+            Function(n) => write!(f, "Y{}", n),
+            ParametricX(n) => write!(f, "X{}T", n),
+            ParametricY(n) => write!(f, "Y{}T", n),
+            Polar(n) => write!(f, "r{}", n),
+            SequenceU => f.write_str("u"),
+            SequenceV => f.write_str("v"),
+            SequenceW => f.write_str("w"),
+        }
+    }
+}
+
+#[test]
+fn identifies_slots() {
+    use alloc::string::ToString;
+
+    let slot = |b: u8| EquationSlot::from_name(&[0x5e, b, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(slot(0x10), Some(EquationSlot::Function(1)));
+    assert_eq!(slot(0x19), Some(EquationSlot::Function(0)));
+    assert_eq!(slot(0x20), Some(EquationSlot::ParametricX(1)));
+    assert_eq!(slot(0x2b), Some(EquationSlot::ParametricY(6)));
+    assert_eq!(slot(0x45), Some(EquationSlot::Polar(6)));
+    assert_eq!(slot(0x81), Some(EquationSlot::SequenceV));
+    assert_eq!(slot(0x1a), None);
+    assert_eq!(EquationSlot::from_name(b"Y1\0\0\0\0\0\0"), None);
+
+    let names: alloc::vec::Vec<_> = [0x13, 0x22, 0x23, 0x40, 0x82]
+        .into_iter()
+        .map(|b| slot(b).unwrap().to_string())
+        .collect();
+    assert_eq!(names, ["Y4", "X2T", "Y2T", "r1", "w"]);
+}
@@ -0,0 +1,190 @@
+//! Flash files, such as applications (8xk) and operating systems (8xu)
+//!
+//! Flash files have a different layout from variable files, so they can't be read with a
+//! [`Reader`](crate::Reader). A flash file consists of a 78-byte header followed by the payload,
+//! which is normally Intel hex text:
+//!
+//! | Offset | Length | Contents                                         |
+//! |--------|--------|--------------------------------------------------|
+//! | 0      | 8      | Signature, `**TIFL**`                            |
+//! | 8      | 2      | Revision, major then minor (BCD)                 |
+//! | 10     | 1      | Flags                                            |
+//! | 11     | 1      | Object type                                      |
+//! | 12     | 4      | Date: day, month and 2-byte year (BCD)           |
+//! | 16     | 1      | Length of name                                   |
+//! | 17     | 8      | Name, padded with NULs                           |
+//! | 25     | 23     | Unused                                           |
+//! | 48     | 1      | Device type (`0x73` for the TI-83 Plus family)   |
+//! | 49     | 1      | Data type (`0x24` for applications)              |
+//! | 50     | 24     | Unused                                           |
+//! | 74     | 4      | Length of payload                                |
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{read_field, ReadError};
+use crate::io::Read;
+
+/// The signature at the beginning of every flash file.
+pub(crate) const SIGNATURE: &[u8; 8] = b"**TIFL**";
+
+/// Length of the flash file header.
+const HEADER_LEN: usize = 78;
+
+/// Data type of flash applications.
+pub const DATA_TYPE_APP: u8 = 0x24;
+/// Data type of operating systems.
+pub const DATA_TYPE_OS: u8 = 0x23;
+
+/// Reads flash files.
+///
+/// The entire payload is read into memory when the reader is constructed, since flash files are
+/// small enough that this is not a problem and the page count can only be determined from the
+/// data.
+#[derive(Debug, Clone)]
+pub struct FlashReader {
+    header: [u8; HEADER_LEN],
+    data: Vec<u8>,
+}
+
+impl FlashReader {
+    /// Read a flash file from the provided input.
+    ///
+    /// Returns [`ReadError::NotFlash`] if the input doesn't begin with a flash file signature, or
+    /// [`ReadError::Truncated`] if it ends before the declared length of the payload.
+    pub fn new<R: Read>(mut r: R) -> crate::io::Result<Self> {
+        let mut header = [0u8; HEADER_LEN];
+        read_field(&mut r, &mut header[..8], "signature")?;
+        if &header[..8] != SIGNATURE {
+            let mut signature = [0u8; 8];
+            signature.copy_from_slice(&header[..8]);
+            return Err(ReadError::NotFlash(signature).into());
+        }
+        read_field(&mut r, &mut header[8..], "flash header")?;
+
+        let data_len = u32::from_le_bytes([header[74], header[75], header[76], header[77]]);
+        // The length is untrusted, so don't allocate it all up front
+        let mut data = Vec::new();
+        r.take(data_len as u64).read_to_end(&mut data)?;
+        if data.len() != data_len as usize {
+            return Err(ReadError::Truncated {
+                expected: data_len as usize,
+                at: "flash data",
+            }
+            .into());
+        }
+
+        Ok(FlashReader { header, data })
+    }
+
+    /// Get the revision of the contents, as major and minor version numbers in BCD.
+    pub fn revision(&self) -> (u8, u8) {
+        (self.header[8], self.header[9])
+    }
+
+    /// Get the name of the contents, such as the name of an application.
+    pub fn name(&self) -> &[u8] {
+        let len = core::cmp::min(self.header[16] as usize, 8);
+        &self.header[17..17 + len]
+    }
+
+    /// Get the name of the contents as a string, replacing non-ASCII bytes with U+FFFD.
+    pub fn name_str(&self) -> String {
+        self.name()
+            .iter()
+            .take_while(|&&b| b != 0)
+            .map(|&b| {
+                if b.is_ascii() {
+                    b as char
+                } else {
+                    char::REPLACEMENT_CHARACTER
+                }
+            })
+            .collect()
+    }
+
+    /// Get the device type the file is for, which is `0x73` for the TI-83 Plus family.
+    pub fn device_type(&self) -> u8 {
+        self.header[48]
+    }
+
+    /// Get the type of data in the file, such as [`DATA_TYPE_APP`] or [`DATA_TYPE_OS`].
+    pub fn data_type(&self) -> u8 {
+        self.header[49]
+    }
+
+    /// Get the payload, which is normally Intel hex text.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the reader, returning the payload.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Return the number of flash pages in the payload.
+    ///
+    /// Each page begins with an extended segment address record (type 2), so this counts those
+    /// records up to the end-of-file record. Returns `None` if the payload isn't valid Intel hex.
+    pub fn page_count(&self) -> Option<usize> {
+        let mut pages = 0;
+        for line in self.data.split(|&b| b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            match record_type(line)? {
+                1 => break,
+                2 => pages += 1,
+                _ => {}
+            }
+        }
+        Some(pages)
+    }
+}
+
+/// Return the type of an Intel hex record, or `None` if it's malformed.
+fn record_type(record: &[u8]) -> Option<u8> {
+    // Start code, byte count, 2-byte address, record type
+    if record.len() < 11 || record[0] != b':' {
+        return None;
+    }
+    let hex = core::str::from_utf8(&record[7..9]).ok()?;
+    u8::from_str_radix(hex, 16).ok()
+}
+
+#[test]
+fn reads_apps() {
+    let payload = b":0200000200FBF9\r\n:10408000800F0000B1E880120108A5004795700D66\r\n\
+                    :0200000200FAFA\r\n:00000001FF\r\n";
+    let mut file = Vec::from(&SIGNATURE[..]);
+    file.extend_from_slice(&[1, 0x02, 0, 0x88, 0x14, 0x10, 0x20, 0x20, 4]);
+    file.extend_from_slice(b"TEST\0\0\0\0");
+    file.extend_from_slice(&[0; 23]);
+    file.extend_from_slice(&[0x73, DATA_TYPE_APP]);
+    file.extend_from_slice(&[0; 24]);
+    file.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    file.extend_from_slice(payload);
+
+    let app = FlashReader::new(&file[..]).unwrap();
+    assert_eq!(app.revision(), (1, 2));
+    assert_eq!(app.name(), b"TEST");
+    assert_eq!(app.name_str(), "TEST");
+    assert_eq!(app.device_type(), 0x73);
+    assert_eq!(app.data_type(), DATA_TYPE_APP);
+    assert_eq!(app.data(), payload);
+    assert_eq!(app.page_count(), Some(2));
+
+    let truncated = FlashReader::new(&file[..file.len() - 1]).unwrap_err();
+    assert_eq!(truncated.kind(), crate::io::ErrorKind::UnexpectedEof);
+    assert!(matches!(
+        *FlashReader::new(&b"**TI83F*"[..])
+            .unwrap_err()
+            .into_inner()
+            .unwrap()
+            .downcast::<ReadError>()
+            .unwrap(),
+        ReadError::NotFlash(_)
+    ));
+}
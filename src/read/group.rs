@@ -0,0 +1,182 @@
+//! Parsing of the members of group variables.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::ReadError;
+use crate::VariableType;
+
+/// A variable contained in a group.
+///
+/// A group's data is a concatenation of entries, one for each member variable. Each entry
+/// resembles a VAT entry from the calculator, followed immediately by the variable's data:
+///
+/// | Size | Field |
+/// |------|-------|
+/// | 1    | Type, in the low 5 bits. The upper 3 bits are flags. |
+/// | 1    | Reserved (T2) |
+/// | 1    | Version |
+/// | 2    | Data address (meaningless outside the calculator) |
+/// | 1    | Flash page: nonzero if the variable is archived |
+/// | 1    | Name length, only for programs, AppVars and groups |
+/// | n    | Name: as many bytes as the name length, or 3 bytes for other types |
+/// | ...  | Variable data, in the same format as in a variable file |
+///
+/// There is no explicit length for the variable data, so its size is computed from the variable
+/// type and data.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GroupMember {
+    ty: VariableType,
+    name: [u8; 8],
+    version: u8,
+    archived: bool,
+    data: Vec<u8>,
+}
+
+impl GroupMember {
+    /// Get the type of this variable.
+    pub fn ty(&self) -> VariableType {
+        self.ty
+    }
+
+    /// Get this variable's name, padded with NULs to 8 bytes.
+    pub fn name(&self) -> &[u8] {
+        self.name.as_slice()
+    }
+
+    /// Get this variable's version byte.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Return whether this variable is archived, as indicated by a nonzero flash page.
+    pub fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    /// Get this variable's data.
+    ///
+    /// As with [`Reader`](super::Reader), the length prefix of length-prefixed types is not
+    /// included.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the member, returning its data.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Return whether a variable of the given type has a length-prefixed name in a group entry.
+pub(crate) fn has_named_entry(ty: VariableType) -> bool {
+    use VariableType::*;
+    matches!(
+        ty,
+        Program | ProtectedProgram | AppVar | Group | TemporaryProgram
+    )
+}
+
+fn take<'a>(data: &mut &'a [u8], n: usize) -> Result<&'a [u8], ReadError> {
+    if data.len() < n {
+        return Err(ReadError::MalformedGroup(
+            "member data extends past the end of the group",
+        ));
+    }
+    let (head, tail) = data.split_at(n);
+    *data = tail;
+    Ok(head)
+}
+
+fn read16(data: &mut &[u8]) -> Result<u16, ReadError> {
+    let b = take(data, 2)?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+/// Compute the size of the data for a variable of the given type, with `data` positioned at
+/// the beginning of its data.
+pub(crate) fn data_size(ty: VariableType, data: &[u8]) -> Result<usize, ReadError> {
+    use VariableType::*;
+
+    let mut peek = data;
+    Ok(match ty {
+        Real => 9,
+        Complex => 18,
+        List => 2 + 9 * read16(&mut peek)? as usize,
+        ComplexList => 2 + 18 * read16(&mut peek)? as usize,
+        Matrix => {
+            let dims = take(&mut peek, 2)?;
+            2 + 9 * dims[0] as usize * dims[1] as usize
+        }
+        _ if ty.has_length_prefix() == Some(true) => 2 + read16(&mut peek)? as usize,
+        _ => {
+            return Err(ReadError::MalformedGroup(
+                "member has a type with unknown size",
+            ))
+        }
+    })
+}
+
+/// Parse the members out of a group's data.
+pub(super) fn parse_members(mut data: &[u8]) -> Result<Vec<GroupMember>, ReadError> {
+    let mut members = vec![];
+
+    while !data.is_empty() {
+        let entry = take(&mut data, 6)?;
+        let ty = VariableType::from_byte(entry[0] & 0x1f)
+            .ok_or(ReadError::UnrecognizedType(entry[0] & 0x1f))?;
+        let version = entry[2];
+        let archived = entry[5] != 0;
+
+        let name_len = if has_named_entry(ty) {
+            take(&mut data, 1)?[0] as usize
+        } else {
+            3
+        };
+        if name_len > 8 {
+            return Err(ReadError::MalformedGroup("member name is too long"));
+        }
+        let mut name = [0u8; 8];
+        name[..name_len].copy_from_slice(take(&mut data, name_len)?);
+
+        let size = data_size(ty, data)?;
+        let var_data = take(&mut data, size)?;
+        let var_data = if ty.has_length_prefix() == Some(true) {
+            &var_data[2..]
+        } else {
+            var_data
+        };
+
+        members.push(GroupMember {
+            ty,
+            name,
+            version,
+            archived,
+            data: var_data.to_vec(),
+        });
+    }
+
+    Ok(members)
+}
+
+#[test]
+fn parses_members() {
+    assert_eq!(parse_members(&[]).unwrap(), vec![]);
+
+    let data = b"\x05\0\0\0\0\0\x03ABC\x02\0\xbb\x6d\
+                 \x00\0\0\0\0\0X\0\0\x00\x80\x10\0\0\0\0\0\0";
+    let members = parse_members(data).unwrap();
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].ty(), VariableType::Program);
+    assert_eq!(members[0].name(), b"ABC\0\0\0\0\0");
+    assert_eq!(members[0].data(), b"\xbb\x6d");
+    assert_eq!(members[1].ty(), VariableType::Real);
+    assert_eq!(members[1].name(), b"X\0\0\0\0\0\0\0");
+    assert_eq!(members[1].data(), b"\x00\x80\x10\0\0\0\0\0\0");
+
+    // Truncated data
+    assert!(matches!(
+        parse_members(&data[..data.len() - 1]),
+        Err(ReadError::MalformedGroup(_))
+    ));
+}
@@ -0,0 +1,131 @@
+//! Conversion of tokenized data to text.
+
+use alloc::format;
+use alloc::string::String;
+
+use super::DecodeError;
+use crate::tokens::{self, TokenTable};
+
+/// Convert tokenized program data to TI-BASIC source text.
+///
+/// Tokens are translated to the text displayed by a calculator, with newlines between lines and
+/// Unicode used for special characters like `→`. Tokens that aren't known are written as their
+/// value in hexadecimal between square brackets, like `[0xBB6D]`.
+///
+/// Returns [`DecodeError::TruncatedToken`] if the data ends with the first byte of a two-byte
+/// token.
+pub fn detokenize(bytes: &[u8]) -> Result<String, DecodeError> {
+    detokenize_with(bytes, TokenTable::builtin())
+}
+
+/// Convert tokenized program data to TI-BASIC source text, using the given token table.
+///
+/// This is like [`detokenize`], but tokens are looked up in `table` instead of the built-in
+/// table.
+pub fn detokenize_with(bytes: &[u8], table: &TokenTable) -> Result<String, DecodeError> {
+    let mut out = String::with_capacity(bytes.len());
+    let mut bytes = bytes.iter();
+
+    while let Some(&b) = bytes.next() {
+        let token = if tokens::is_prefix(b) {
+            match bytes.next() {
+                Some(&b2) => u16::from_be_bytes([b, b2]),
+                None => return Err(DecodeError::TruncatedToken(b)),
+            }
+        } else {
+            b as u16
+        };
+
+        match table.get(token) {
+            Some(s) => out.push_str(s),
+            None if token > 0xff => out.push_str(&format!("[0x{:04X}]", token)),
+            None => out.push_str(&format!("[0x{:02X}]", token)),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Convert the data of a [`String`](crate::VariableType::String) variable to text.
+///
+/// `bytes` should be the variable data as returned by a [`Reader`](crate::Reader), which
+/// already excludes the length prefix. Strings are tokenized in the same way as programs, but
+/// since they're usually only displayed this does not fail: unknown or truncated tokens are
+/// replaced with U+FFFD REPLACEMENT CHARACTER.
+pub fn decode_string(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    let mut bytes = bytes.iter();
+
+    while let Some(&b) = bytes.next() {
+        let token = if tokens::is_prefix(b) {
+            match bytes.next() {
+                Some(&b2) => u16::from_be_bytes([b, b2]),
+                None => {
+                    out.push(char::REPLACEMENT_CHARACTER);
+                    break;
+                }
+            }
+        } else {
+            b as u16
+        };
+
+        match TokenTable::builtin().get(token) {
+            Some(s) => out.push_str(s),
+            None => out.push(char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    out
+}
+
+/// Convert the data of an [`Equation`](crate::VariableType::Equation) variable to text.
+///
+/// Equations are tokenized expressions such as `X²+2`, decoded in the same way as
+/// [`decode_string`]. The data doesn't say which equation it is: that's given by the variable
+/// name, as returned by [`Reader::equation_slot`](crate::Reader::equation_slot).
+pub fn decode_equation(bytes: &[u8]) -> String {
+    decode_string(bytes)
+}
+
+#[test]
+fn detokenizes_programs() {
+    assert_eq!(
+        detokenize(b"\xdd\x41\x3f\xde\x2a\x48\x49\x2a\x2b\x41\x3f\x41\x04\x5b\x3f\xaa\x00")
+            .unwrap(),
+        "Prompt A\nDisp \"HI\",A\nA→θ\nStr1"
+    );
+    assert_eq!(
+        detokenize(b"\xbb\x0a\x31\x2b\x36\x11").unwrap(),
+        "randInt(1,6)"
+    );
+    assert_eq!(detokenize(b"\x26\xbb\xff").unwrap(), "[0x26][0xBBFF]");
+    assert_eq!(
+        detokenize(b"\x41\xbb"),
+        Err(DecodeError::TruncatedToken(0xbb))
+    );
+}
+
+#[test]
+fn detokenizes_with_custom_tables() {
+    let mut table = TokenTable::default();
+    table.insert(0x04, "->");
+    table.remove(0x41);
+    assert_eq!(
+        detokenize_with(b"\x41\x04\x42", &table).unwrap(),
+        "[0x41]->B"
+    );
+    assert_eq!(detokenize(b"\x41\x04\x42").unwrap(), "A→B");
+}
+
+#[test]
+fn decodes_equations() {
+    assert_eq!(decode_equation(b"\x58\x0d\x70\x32"), "X²+2");
+    assert_eq!(decode_equation(b""), "");
+}
+
+#[test]
+fn decodes_strings() {
+    assert_eq!(decode_string(b"\x48\x45\x4c\x4c\x4f\x29\x5b"), "HELLO θ");
+    assert_eq!(decode_string(b"\xbb\xb0\xbb\xb1"), "ab");
+    assert_eq!(decode_string(b"\x41\x26\xbb"), "A\u{fffd}\u{fffd}");
+}
@@ -0,0 +1,125 @@
+//! serde support for [`VariableType`], enabled by the `serde` feature.
+//!
+//! Types are serialized as the names of their variants, like `"Program"`. When deserializing,
+//! either a name or the numeric type byte is accepted.
+//!
+//! Deserialization relies on the format to say which of the two it holds, so only
+//! self-describing formats (like JSON) are supported.
+
+#[cfg(test)]
+use alloc::string::ToString;
+use serde::de::{self, Deserialize, Deserializer, Unexpected, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::VariableType;
+
+/// Every variable type and its variant name, in order of type byte.
+const VARIANTS: &[(VariableType, &str)] = {
+    use VariableType::*;
+    &[
+        (Real, "Real"),
+        (List, "List"),
+        (Matrix, "Matrix"),
+        (Equation, "Equation"),
+        (String, "String"),
+        (Program, "Program"),
+        (ProtectedProgram, "ProtectedProgram"),
+        (Picture, "Picture"),
+        (GDB, "GDB"),
+        (Unknown, "Unknown"),
+        (UnknownEquation, "UnknownEquation"),
+        (NewEquation, "NewEquation"),
+        (Complex, "Complex"),
+        (ComplexList, "ComplexList"),
+        (Undefined, "Undefined"),
+        (Window, "Window"),
+        (Zoom, "Zoom"),
+        (TableSetup, "TableSetup"),
+        (LCD, "LCD"),
+        (Backup, "Backup"),
+        (AppVar, "AppVar"),
+        (TemporaryProgram, "TemporaryProgram"),
+        (Group, "Group"),
+    ]
+};
+
+/// Serializes as a unit variant named for the type, with the type byte as its index.
+impl Serialize for VariableType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let &(_, name) = VARIANTS
+            .iter()
+            .find(|(ty, _)| ty == self)
+            .expect("all variable types should have names");
+        serializer.serialize_unit_variant("VariableType", self.to_byte() as u32, name)
+    }
+}
+
+struct VariableTypeVisitor;
+
+impl<'de> Visitor<'de> for VariableTypeVisitor {
+    type Value = VariableType;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("a variable type name or type byte")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        VARIANTS
+            .iter()
+            .find(|&&(_, name)| name == v)
+            .map(|&(ty, _)| ty)
+            .ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        u8::try_from(v)
+            .ok()
+            .and_then(VariableType::from_byte)
+            .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        u64::try_from(v)
+            .map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+            .and_then(|v| self.visit_u64(v))
+    }
+}
+
+/// Deserializes from a variant name or type byte. The format must be self-describing.
+impl<'de> Deserialize<'de> for VariableType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(VariableTypeVisitor)
+    }
+}
+
+#[test]
+fn variable_types_round_trip() {
+    for &(ty, name) in VARIANTS {
+        let json = serde_json::to_string(&ty).unwrap();
+        assert_eq!(json, format!("\"{}\"", name));
+        assert_eq!(serde_json::from_str::<VariableType>(&json).unwrap(), ty);
+        assert_eq!(
+            serde_json::from_str::<VariableType>(&(ty as u8).to_string()).unwrap(),
+            ty
+        );
+    }
+
+    assert!(serde_json::from_str::<VariableType>("\"Bogus\"").is_err());
+    assert!(serde_json::from_str::<VariableType>("20").is_err());
+    assert!(serde_json::from_str::<VariableType>("256").is_err());
+}
+
+#[test]
+fn all_variable_types_have_names() {
+    let types = (0..=u8::MAX).filter_map(VariableType::from_byte);
+    assert_eq!(types.clone().count(), VARIANTS.len());
+    for ty in types {
+        assert!(
+            VARIANTS
+                .iter()
+                .any(|&(named, name)| named == ty && name == format!("{:?}", ty)),
+            "{:?} should be named",
+            ty
+        );
+    }
+}
@@ -0,0 +1,750 @@
+//! TI-BASIC token table
+//!
+//! Tokens are identified by their value: single-byte tokens are in the range 0-0xff, and
+//! two-byte tokens have the prefix byte in the upper 8 bits. The table covers the tokens
+//! supported by TI-84 Plus OS 2.55MP, and uses Unicode for the characters that the calculator
+//! displays specially (such as `→` and `θ`).
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// A table of TI-BASIC tokens and their text, used to convert between tokenized data and text.
+///
+/// The [built-in table](TokenTable::builtin) covers the tokens supported by TI-84 Plus OS
+/// 2.55MP. Tokens can be added or replaced to support other OS versions or calculators:
+///
+/// ```
+/// # use tifiles::{TokenTable, read::tokens::detokenize_with};
+/// let mut table = TokenTable::default();
+/// table.insert(0xef1e, "MyToken");
+/// assert_eq!(detokenize_with(b"\xef\x1e", &table).unwrap(), "MyToken");
+/// ```
+///
+/// Which bytes begin two-byte tokens is fixed by the calculator, so two-byte tokens added to a
+/// table must begin with one of the prefix bytes that two-byte tokens in the built-in table use:
+/// others can never be matched when detokenizing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenTable {
+    /// Built-in tokens, sorted by value
+    base: &'static [(u16, &'static str)],
+    /// Changes to the built-in tokens: `None` for tokens that have been removed
+    overrides: BTreeMap<u16, Option<String>>,
+}
+
+static BUILTIN: TokenTable = TokenTable {
+    base: TOKENS,
+    overrides: BTreeMap::new(),
+};
+
+impl TokenTable {
+    /// Return the built-in token table.
+    pub fn builtin() -> &'static TokenTable {
+        &BUILTIN
+    }
+
+    /// Create a table containing no tokens.
+    pub fn empty() -> Self {
+        TokenTable {
+            base: &[],
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Look up the text of a token by value.
+    pub fn get(&self, token: u16) -> Option<&str> {
+        match self.overrides.get(&token) {
+            Some(text) => text.as_deref(),
+            None => self
+                .base
+                .binary_search_by_key(&token, |&(value, _)| value)
+                .ok()
+                .map(|i| self.base[i].1),
+        }
+    }
+
+    /// Add a token to the table, replacing any existing token with the same value.
+    pub fn insert(&mut self, token: u16, text: impl Into<String>) {
+        self.overrides.insert(token, Some(text.into()));
+    }
+
+    /// Remove a token from the table, if present.
+    pub fn remove(&mut self, token: u16) {
+        self.overrides.insert(token, None);
+    }
+
+    /// Iterate over every token in the table and its text.
+    ///
+    /// Tokens from the built-in table come first, in order of value, followed by any that have
+    /// been added in order of value.
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &str)> + '_ {
+        let base = self
+            .base
+            .iter()
+            .filter(|(value, _)| !self.overrides.contains_key(value))
+            .map(|&(value, text)| (value, text));
+        let added = self
+            .overrides
+            .iter()
+            .filter_map(|(&value, text)| Some((value, text.as_deref()?)));
+        base.chain(added)
+    }
+}
+
+impl Default for TokenTable {
+    /// Create a copy of the [built-in table](TokenTable::builtin).
+    fn default() -> Self {
+        BUILTIN.clone()
+    }
+}
+
+/// Return whether a byte begins a two-byte token.
+pub(crate) fn is_prefix(b: u8) -> bool {
+    matches!(
+        b,
+        0x5c | 0x5d | 0x5e | 0x60 | 0x61 | 0x62 | 0x63 | 0x7e | 0xaa | 0xbb | 0xef
+    )
+}
+
+/// All known tokens and their text, sorted by value.
+static TOKENS: &[(u16, &str)] = &[
+    (0x01, "►DMS"),
+    (0x02, "►Dec"),
+    (0x03, "►Frac"),
+    (0x04, "→"),
+    (0x05, "Boxplot"),
+    (0x06, "["),
+    (0x07, "]"),
+    (0x08, "{"),
+    (0x09, "}"),
+    (0x0a, "ʳ"),
+    (0x0b, "°"),
+    (0x0c, "⁻¹"),
+    (0x0d, "²"),
+    (0x0e, "ᵀ"),
+    (0x0f, "³"),
+    (0x10, "("),
+    (0x11, ")"),
+    (0x12, "round("),
+    (0x13, "pxl-Test("),
+    (0x14, "augment("),
+    (0x15, "rowSwap("),
+    (0x16, "row+("),
+    (0x17, "*row("),
+    (0x18, "*row+("),
+    (0x19, "max("),
+    (0x1a, "min("),
+    (0x1b, "R►Pr("),
+    (0x1c, "R►Pθ("),
+    (0x1d, "P►Rx("),
+    (0x1e, "P►Ry("),
+    (0x1f, "median("),
+    (0x20, "randM("),
+    (0x21, "mean("),
+    (0x22, "solve("),
+    (0x23, "seq("),
+    (0x24, "fnInt("),
+    (0x25, "nDeriv("),
+    (0x27, "fMin("),
+    (0x28, "fMax("),
+    (0x29, " "),
+    (0x2a, "\""),
+    (0x2b, ","),
+    (0x2c, "𝑖"),
+    (0x2d, "!"),
+    (0x2e, "CubicReg "),
+    (0x2f, "QuartReg "),
+    (0x30, "0"),
+    (0x31, "1"),
+    (0x32, "2"),
+    (0x33, "3"),
+    (0x34, "4"),
+    (0x35, "5"),
+    (0x36, "6"),
+    (0x37, "7"),
+    (0x38, "8"),
+    (0x39, "9"),
+    (0x3a, "."),
+    (0x3b, "ᴇ"),
+    (0x3c, " or "),
+    (0x3d, " xor "),
+    (0x3e, ":"),
+    (0x3f, "\n"),
+    (0x40, " and "),
+    (0x41, "A"),
+    (0x42, "B"),
+    (0x43, "C"),
+    (0x44, "D"),
+    (0x45, "E"),
+    (0x46, "F"),
+    (0x47, "G"),
+    (0x48, "H"),
+    (0x49, "I"),
+    (0x4a, "J"),
+    (0x4b, "K"),
+    (0x4c, "L"),
+    (0x4d, "M"),
+    (0x4e, "N"),
+    (0x4f, "O"),
+    (0x50, "P"),
+    (0x51, "Q"),
+    (0x52, "R"),
+    (0x53, "S"),
+    (0x54, "T"),
+    (0x55, "U"),
+    (0x56, "V"),
+    (0x57, "W"),
+    (0x58, "X"),
+    (0x59, "Y"),
+    (0x5a, "Z"),
+    (0x5b, "θ"),
+    (0x5f, "prgm"),
+    (0x64, "Radian"),
+    (0x65, "Degree"),
+    (0x66, "Normal"),
+    (0x67, "Sci"),
+    (0x68, "Eng"),
+    (0x69, "Float"),
+    (0x6a, "="),
+    (0x6b, "<"),
+    (0x6c, ">"),
+    (0x6d, "≤"),
+    (0x6e, "≥"),
+    (0x6f, "≠"),
+    (0x70, "+"),
+    (0x71, "-"),
+    (0x72, "Ans"),
+    (0x73, "Fix "),
+    (0x74, "Horiz"),
+    (0x75, "Full"),
+    (0x76, "Func"),
+    (0x77, "Param"),
+    (0x78, "Polar"),
+    (0x79, "Seq"),
+    (0x7a, "IndpntAuto"),
+    (0x7b, "IndpntAsk"),
+    (0x7c, "DependAuto"),
+    (0x7d, "DependAsk"),
+    (0x7f, "□"),
+    (0x80, "﹢"),
+    (0x81, "·"),
+    (0x82, "*"),
+    (0x83, "/"),
+    (0x84, "Trace"),
+    (0x85, "ClrDraw"),
+    (0x86, "ZStandard"),
+    (0x87, "ZTrig"),
+    (0x88, "ZBox"),
+    (0x89, "Zoom In"),
+    (0x8a, "Zoom Out"),
+    (0x8b, "ZSquare"),
+    (0x8c, "ZInteger"),
+    (0x8d, "ZPrevious"),
+    (0x8e, "ZDecimal"),
+    (0x8f, "ZoomStat"),
+    (0x90, "ZoomRcl"),
+    (0x91, "PrintScreen"),
+    (0x92, "ZoomSto"),
+    (0x93, "Text("),
+    (0x94, " nPr "),
+    (0x95, " nCr "),
+    (0x96, "FnOn "),
+    (0x97, "FnOff "),
+    (0x98, "StorePic "),
+    (0x99, "RecallPic "),
+    (0x9a, "StoreGDB "),
+    (0x9b, "RecallGDB "),
+    (0x9c, "Line("),
+    (0x9d, "Vertical "),
+    (0x9e, "Pt-On("),
+    (0x9f, "Pt-Off("),
+    (0xa0, "Pt-Change("),
+    (0xa1, "Pxl-On("),
+    (0xa2, "Pxl-Off("),
+    (0xa3, "Pxl-Change("),
+    (0xa4, "Shade("),
+    (0xa5, "Circle("),
+    (0xa6, "Horizontal "),
+    (0xa7, "Tangent("),
+    (0xa8, "DrawInv "),
+    (0xa9, "DrawF "),
+    (0xab, "rand"),
+    (0xac, "π"),
+    (0xad, "getKey"),
+    (0xae, "'"),
+    (0xaf, "?"),
+    (0xb0, "⁻"),
+    (0xb1, "int("),
+    (0xb2, "abs("),
+    (0xb3, "det("),
+    (0xb4, "identity("),
+    (0xb5, "dim("),
+    (0xb6, "sum("),
+    (0xb7, "prod("),
+    (0xb8, "not("),
+    (0xb9, "iPart("),
+    (0xba, "fPart("),
+    (0xbc, "√("),
+    (0xbd, "³√("),
+    (0xbe, "ln("),
+    (0xbf, "e^("),
+    (0xc0, "log("),
+    (0xc1, "₁₀^("),
+    (0xc2, "sin("),
+    (0xc3, "sin⁻¹("),
+    (0xc4, "cos("),
+    (0xc5, "cos⁻¹("),
+    (0xc6, "tan("),
+    (0xc7, "tan⁻¹("),
+    (0xc8, "sinh("),
+    (0xc9, "sinh⁻¹("),
+    (0xca, "cosh("),
+    (0xcb, "cosh⁻¹("),
+    (0xcc, "tanh("),
+    (0xcd, "tanh⁻¹("),
+    (0xce, "If "),
+    (0xcf, "Then"),
+    (0xd0, "Else"),
+    (0xd1, "While "),
+    (0xd2, "Repeat "),
+    (0xd3, "For("),
+    (0xd4, "End"),
+    (0xd5, "Return"),
+    (0xd6, "Lbl "),
+    (0xd7, "Goto "),
+    (0xd8, "Pause "),
+    (0xd9, "Stop"),
+    (0xda, "IS>("),
+    (0xdb, "DS<("),
+    (0xdc, "Input "),
+    (0xdd, "Prompt "),
+    (0xde, "Disp "),
+    (0xdf, "DispGraph"),
+    (0xe0, "Output("),
+    (0xe1, "ClrHome"),
+    (0xe2, "Fill("),
+    (0xe3, "SortA("),
+    (0xe4, "SortD("),
+    (0xe5, "DispTable"),
+    (0xe6, "Menu("),
+    (0xe7, "Send("),
+    (0xe8, "Get("),
+    (0xe9, "PlotsOn "),
+    (0xea, "PlotsOff "),
+    (0xeb, "ʟ"),
+    (0xec, "Plot1("),
+    (0xed, "Plot2("),
+    (0xee, "Plot3("),
+    (0xf0, "^"),
+    (0xf1, "×√"),
+    (0xf2, "1-Var Stats "),
+    (0xf3, "2-Var Stats "),
+    (0xf4, "LinReg(a+bx) "),
+    (0xf5, "ExpReg "),
+    (0xf6, "LnReg "),
+    (0xf7, "PwrReg "),
+    (0xf8, "Med-Med "),
+    (0xf9, "QuadReg "),
+    (0xfa, "ClrList "),
+    (0xfb, "ClrTable"),
+    (0xfc, "Histogram"),
+    (0xfd, "xyLine"),
+    (0xfe, "Scatter"),
+    (0xff, "LinReg(ax+b) "),
+    (0x5c00, "[A]"),
+    (0x5c01, "[B]"),
+    (0x5c02, "[C]"),
+    (0x5c03, "[D]"),
+    (0x5c04, "[E]"),
+    (0x5c05, "[F]"),
+    (0x5c06, "[G]"),
+    (0x5c07, "[H]"),
+    (0x5c08, "[I]"),
+    (0x5c09, "[J]"),
+    (0x5d00, "L₁"),
+    (0x5d01, "L₂"),
+    (0x5d02, "L₃"),
+    (0x5d03, "L₄"),
+    (0x5d04, "L₅"),
+    (0x5d05, "L₆"),
+    (0x5e10, "Y₁"),
+    (0x5e11, "Y₂"),
+    (0x5e12, "Y₃"),
+    (0x5e13, "Y₄"),
+    (0x5e14, "Y₅"),
+    (0x5e15, "Y₆"),
+    (0x5e16, "Y₇"),
+    (0x5e17, "Y₈"),
+    (0x5e18, "Y₉"),
+    (0x5e19, "Y₀"),
+    (0x5e20, "X₁ᴛ"),
+    (0x5e21, "Y₁ᴛ"),
+    (0x5e22, "X₂ᴛ"),
+    (0x5e23, "Y₂ᴛ"),
+    (0x5e24, "X₃ᴛ"),
+    (0x5e25, "Y₃ᴛ"),
+    (0x5e26, "X₄ᴛ"),
+    (0x5e27, "Y₄ᴛ"),
+    (0x5e28, "X₅ᴛ"),
+    (0x5e29, "Y₅ᴛ"),
+    (0x5e2a, "X₆ᴛ"),
+    (0x5e2b, "Y₆ᴛ"),
+    (0x5e40, "r₁"),
+    (0x5e41, "r₂"),
+    (0x5e42, "r₃"),
+    (0x5e43, "r₄"),
+    (0x5e44, "r₅"),
+    (0x5e45, "r₆"),
+    (0x5e80, "u"),
+    (0x5e81, "v"),
+    (0x5e82, "w"),
+    (0x6000, "Pic1"),
+    (0x6001, "Pic2"),
+    (0x6002, "Pic3"),
+    (0x6003, "Pic4"),
+    (0x6004, "Pic5"),
+    (0x6005, "Pic6"),
+    (0x6006, "Pic7"),
+    (0x6007, "Pic8"),
+    (0x6008, "Pic9"),
+    (0x6009, "Pic0"),
+    (0x6100, "GDB1"),
+    (0x6101, "GDB2"),
+    (0x6102, "GDB3"),
+    (0x6103, "GDB4"),
+    (0x6104, "GDB5"),
+    (0x6105, "GDB6"),
+    (0x6106, "GDB7"),
+    (0x6107, "GDB8"),
+    (0x6108, "GDB9"),
+    (0x6109, "GDB0"),
+    (0x6201, "RegEQ"),
+    (0x6202, "n"),
+    (0x6203, "x̄"),
+    (0x6204, "Σx"),
+    (0x6205, "Σx²"),
+    (0x6206, "Sx"),
+    (0x6207, "σx"),
+    (0x6208, "minX"),
+    (0x6209, "maxX"),
+    (0x620a, "minY"),
+    (0x620b, "maxY"),
+    (0x620c, "ȳ"),
+    (0x620d, "Σy"),
+    (0x620e, "Σy²"),
+    (0x620f, "Sy"),
+    (0x6210, "σy"),
+    (0x6211, "Σxy"),
+    (0x6212, "r"),
+    (0x6213, "Med"),
+    (0x6214, "Q₁"),
+    (0x6215, "Q₃"),
+    (0x6216, "a"),
+    (0x6217, "b"),
+    (0x6218, "c"),
+    (0x6219, "d"),
+    (0x621a, "e"),
+    (0x621b, "x₁"),
+    (0x621c, "x₂"),
+    (0x621d, "x₃"),
+    (0x621e, "y₁"),
+    (0x621f, "y₂"),
+    (0x6220, "y₃"),
+    (0x6221, "𝑛"),
+    (0x6222, "p"),
+    (0x6223, "z"),
+    (0x6224, "t"),
+    (0x6225, "χ²"),
+    (0x6226, "𝙵"),
+    (0x6227, "df"),
+    (0x6228, "p̂"),
+    (0x6229, "p̂₁"),
+    (0x622a, "p̂₂"),
+    (0x622b, "x̄₁"),
+    (0x622c, "Sx₁"),
+    (0x622d, "n₁"),
+    (0x622e, "x̄₂"),
+    (0x622f, "Sx₂"),
+    (0x6230, "n₂"),
+    (0x6231, "Sxp"),
+    (0x6232, "lower"),
+    (0x6233, "upper"),
+    (0x6234, "s"),
+    (0x6235, "r²"),
+    (0x6236, "R²"),
+    (0x6237, "Factor df"),
+    (0x6238, "Factor SS"),
+    (0x6239, "Factor MS"),
+    (0x623a, "Error df"),
+    (0x623b, "Error SS"),
+    (0x623c, "Error MS"),
+    (0x6300, "ZXscl"),
+    (0x6301, "ZYscl"),
+    (0x6302, "Xscl"),
+    (0x6303, "Yscl"),
+    (0x6304, "u(𝑛Min)"),
+    (0x6305, "v(𝑛Min)"),
+    (0x6306, "u(𝑛-1)"),
+    (0x6307, "v(𝑛-1)"),
+    (0x6308, "Zu(𝑛Min)"),
+    (0x6309, "Zv(𝑛Min)"),
+    (0x630a, "Xmin"),
+    (0x630b, "Xmax"),
+    (0x630c, "Ymin"),
+    (0x630d, "Ymax"),
+    (0x630e, "Tmin"),
+    (0x630f, "Tmax"),
+    (0x6310, "θmin"),
+    (0x6311, "θmax"),
+    (0x6312, "ZXmin"),
+    (0x6313, "ZXmax"),
+    (0x6314, "ZYmin"),
+    (0x6315, "ZYmax"),
+    (0x6316, "Zθmin"),
+    (0x6317, "Zθmax"),
+    (0x6318, "ZTmin"),
+    (0x6319, "ZTmax"),
+    (0x631a, "TblStart"),
+    (0x631b, "PlotStart"),
+    (0x631c, "ZPlotStart"),
+    (0x631d, "𝑛Max"),
+    (0x631e, "Z𝑛Max"),
+    (0x631f, "𝑛Min"),
+    (0x6320, "Z𝑛Min"),
+    (0x6321, "ΔTbl"),
+    (0x6322, "Tstep"),
+    (0x6323, "θstep"),
+    (0x6324, "ZTstep"),
+    (0x6325, "Zθstep"),
+    (0x6326, "ΔX"),
+    (0x6327, "ΔY"),
+    (0x6328, "XFact"),
+    (0x6329, "YFact"),
+    (0x632a, "TblInput"),
+    (0x632b, "𝗡"),
+    (0x632c, "I%"),
+    (0x632d, "PV"),
+    (0x632e, "PMT"),
+    (0x632f, "FV"),
+    (0x6330, "P/Y"),
+    (0x6331, "C/Y"),
+    (0x6332, "w(𝑛Min)"),
+    (0x6333, "Zw(𝑛Min)"),
+    (0x6334, "PlotStep"),
+    (0x6335, "ZPlotStep"),
+    (0x6336, "Xres"),
+    (0x6337, "ZXres"),
+    (0x7e00, "Sequential"),
+    (0x7e01, "Simul"),
+    (0x7e02, "PolarGC"),
+    (0x7e03, "RectGC"),
+    (0x7e04, "CoordOn"),
+    (0x7e05, "CoordOff"),
+    (0x7e06, "Connected"),
+    (0x7e07, "Dot"),
+    (0x7e08, "AxesOn"),
+    (0x7e09, "AxesOff"),
+    (0x7e0a, "GridOn"),
+    (0x7e0b, "GridOff"),
+    (0x7e0c, "LabelOn"),
+    (0x7e0d, "LabelOff"),
+    (0x7e0e, "Web"),
+    (0x7e0f, "Time"),
+    (0x7e10, "uvAxes"),
+    (0x7e11, "vwAxes"),
+    (0x7e12, "uwAxes"),
+    (0xaa00, "Str1"),
+    (0xaa01, "Str2"),
+    (0xaa02, "Str3"),
+    (0xaa03, "Str4"),
+    (0xaa04, "Str5"),
+    (0xaa05, "Str6"),
+    (0xaa06, "Str7"),
+    (0xaa07, "Str8"),
+    (0xaa08, "Str9"),
+    (0xaa09, "Str0"),
+    (0xbb00, "npv("),
+    (0xbb01, "irr("),
+    (0xbb02, "bal("),
+    (0xbb03, "ΣPrn("),
+    (0xbb04, "ΣInt("),
+    (0xbb05, "►Nom("),
+    (0xbb06, "►Eff("),
+    (0xbb07, "dbd("),
+    (0xbb08, "lcm("),
+    (0xbb09, "gcd("),
+    (0xbb0a, "randInt("),
+    (0xbb0b, "randBin("),
+    (0xbb0c, "sub("),
+    (0xbb0d, "stdDev("),
+    (0xbb0e, "variance("),
+    (0xbb0f, "inString("),
+    (0xbb10, "normalcdf("),
+    (0xbb11, "invNorm("),
+    (0xbb12, "tcdf("),
+    (0xbb13, "χ²cdf("),
+    (0xbb14, "𝙵cdf("),
+    (0xbb15, "binompdf("),
+    (0xbb16, "binomcdf("),
+    (0xbb17, "poissonpdf("),
+    (0xbb18, "poissoncdf("),
+    (0xbb19, "geometpdf("),
+    (0xbb1a, "geometcdf("),
+    (0xbb1b, "normalpdf("),
+    (0xbb1c, "tpdf("),
+    (0xbb1d, "χ²pdf("),
+    (0xbb1e, "𝙵pdf("),
+    (0xbb1f, "randNorm("),
+    (0xbb20, "tvm_Pmt"),
+    (0xbb21, "tvm_I%"),
+    (0xbb22, "tvm_PV"),
+    (0xbb23, "tvm_N"),
+    (0xbb24, "tvm_FV"),
+    (0xbb25, "conj("),
+    (0xbb26, "real("),
+    (0xbb27, "imag("),
+    (0xbb28, "angle("),
+    (0xbb29, "cumSum("),
+    (0xbb2a, "expr("),
+    (0xbb2b, "length("),
+    (0xbb2c, "ΔList("),
+    (0xbb2d, "ref("),
+    (0xbb2e, "rref("),
+    (0xbb2f, "►Rect"),
+    (0xbb30, "►Polar"),
+    (0xbb31, "e"),
+    (0xbb32, "SinReg "),
+    (0xbb33, "Logistic "),
+    (0xbb34, "LinRegTTest "),
+    (0xbb35, "ShadeNorm("),
+    (0xbb36, "Shade_t("),
+    (0xbb37, "Shadeχ²("),
+    (0xbb38, "Shade𝙵("),
+    (0xbb39, "Matr►list("),
+    (0xbb3a, "List►matr("),
+    (0xbb3b, "Z-Test("),
+    (0xbb3c, "T-Test "),
+    (0xbb3d, "2-SampZTest("),
+    (0xbb3e, "1-PropZTest("),
+    (0xbb3f, "2-PropZTest("),
+    (0xbb40, "χ²-Test("),
+    (0xbb41, "ZInterval "),
+    (0xbb42, "2-SampZInt("),
+    (0xbb43, "1-PropZInt("),
+    (0xbb44, "2-PropZInt("),
+    (0xbb45, "GraphStyle("),
+    (0xbb46, "2-SampTTest "),
+    (0xbb47, "2-Samp𝙵Test "),
+    (0xbb48, "TInterval "),
+    (0xbb49, "2-SampTInt "),
+    (0xbb4a, "SetUpEditor "),
+    (0xbb4b, "Pmt_End"),
+    (0xbb4c, "Pmt_Bgn"),
+    (0xbb4d, "Real"),
+    (0xbb4e, "re^θ𝑖"),
+    (0xbb4f, "a+b𝑖"),
+    (0xbb50, "ExprOn"),
+    (0xbb51, "ExprOff"),
+    (0xbb52, "ClrAllLists"),
+    (0xbb53, "GetCalc("),
+    (0xbb54, "DelVar "),
+    (0xbb55, "Equ►String("),
+    (0xbb56, "String►Equ("),
+    (0xbb57, "Clear Entries"),
+    (0xbb58, "Select("),
+    (0xbb59, "ANOVA("),
+    (0xbb5a, "ModBoxplot"),
+    (0xbb5b, "NormProbPlot"),
+    (0xbb64, "G-T"),
+    (0xbb65, "ZoomFit"),
+    (0xbb66, "DiagnosticOn"),
+    (0xbb67, "DiagnosticOff"),
+    (0xbb68, "Archive "),
+    (0xbb69, "UnArchive "),
+    (0xbb6a, "Asm("),
+    (0xbb6b, "AsmComp("),
+    (0xbb6c, "AsmPrgm"),
+    (0xbbb0, "a"),
+    (0xbbb1, "b"),
+    (0xbbb2, "c"),
+    (0xbbb3, "d"),
+    (0xbbb4, "e"),
+    (0xbbb5, "f"),
+    (0xbbb6, "g"),
+    (0xbbb7, "h"),
+    (0xbbb8, "i"),
+    (0xbbb9, "j"),
+    (0xbbba, "k"),
+    (0xbbbc, "l"),
+    (0xbbbd, "m"),
+    (0xbbbe, "n"),
+    (0xbbbf, "o"),
+    (0xbbc0, "p"),
+    (0xbbc1, "q"),
+    (0xbbc2, "r"),
+    (0xbbc3, "s"),
+    (0xbbc4, "t"),
+    (0xbbc5, "u"),
+    (0xbbc6, "v"),
+    (0xbbc7, "w"),
+    (0xbbc8, "x"),
+    (0xbbc9, "y"),
+    (0xbbca, "z"),
+    (0xef00, "setDate("),
+    (0xef01, "setTime("),
+    (0xef02, "checkTmr("),
+    (0xef03, "setDtFmt("),
+    (0xef04, "setTmFmt("),
+    (0xef05, "timeCnv("),
+    (0xef06, "dayOfWk("),
+    (0xef07, "getDtStr("),
+    (0xef08, "getTmStr("),
+    (0xef09, "getDate"),
+    (0xef0a, "getTime"),
+    (0xef0b, "startTmr"),
+    (0xef0c, "getDtFmt"),
+    (0xef0d, "getTmFmt"),
+    (0xef0e, "isClockOn"),
+    (0xef0f, "ClockOff"),
+    (0xef10, "ClockOn"),
+    (0xef11, "OpenLib("),
+    (0xef12, "ExecLib"),
+    (0xef13, "invT("),
+    (0xef14, "χ²GOF-Test("),
+    (0xef15, "LinRegTInt "),
+    (0xef16, "Manual-Fit "),
+    (0xef17, "ZQuadrant1"),
+    (0xef18, "ZFrac1/2"),
+    (0xef19, "ZFrac1/3"),
+    (0xef1a, "ZFrac1/4"),
+    (0xef1b, "ZFrac1/5"),
+    (0xef1c, "ZFrac1/8"),
+    (0xef1d, "ZFrac1/10"),
+];
+
+#[test]
+fn table_is_sorted() {
+    assert!(TOKENS.windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[test]
+fn tables_can_be_modified() {
+    let mut table = TokenTable::default();
+    assert_eq!(table, *TokenTable::builtin());
+    assert_eq!(table.get(0x04), Some("→"));
+    assert_eq!(table.iter().count(), TOKENS.len());
+
+    table.insert(0x04, "->");
+    table.insert(0xef1e, "New");
+    table.remove(0xbb0a);
+    assert_eq!(table.get(0x04), Some("->"));
+    assert_eq!(table.get(0xef1e), Some("New"));
+    assert_eq!(table.get(0xbb0a), None);
+    assert_eq!(table.iter().count(), TOKENS.len());
+    assert_eq!(table.iter().last(), Some((0xef1e, "New")));
+    assert_eq!(TokenTable::builtin().get(0x04), Some("→"));
+
+    let mut empty = TokenTable::empty();
+    assert_eq!(empty.iter().next(), None);
+    empty.insert(0x41, "A");
+    assert_eq!(empty.iter().collect::<alloc::vec::Vec<_>>(), [(0x41, "A")]);
+}
@@ -1,18 +1,173 @@
-use std::io::{Seek, SeekFrom, Write};
+use alloc::string::String;
+use alloc::vec::Vec;
 
-use super::{VariableType, MAX_DATA};
+use crate::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use super::{Checksum, VariableType, MAX_DATA};
+
+mod encode;
+#[cfg(feature = "detokenize")]
+pub mod tokens;
+
+pub use encode::{
+    encode_complex, encode_complex_list, encode_real, encode_table_setup, encode_window,
+    EncodeError,
+};
 
 /// Custom IO error variants for writing variables.
 ///
-/// These are returned in a `Custom` [`std::io::Error`].
+/// These are returned in an [`io::Error`](crate::io::Error) of kind
+/// [`Other`](crate::io::ErrorKind::Other), and can be extracted from one with
+/// [`from_io`](WriteError::from_io):
+///
+/// ```
+/// # use tifiles::{VariableType, Writer, write::WriteError};
+/// let err = Writer::new(tifiles::io::Cursor::new(vec![]), VariableType::Program, "bad", false)
+///     .err()
+///     .unwrap();
+/// assert!(matches!(WriteError::from_io(&err), Some(WriteError::InvalidName)));
+/// ```
 #[derive(thiserror::Error, Debug)]
 pub enum WriteError {
     /// Too much data was written to a variable, in excess of what can be represented in a file.
-    #[error("Variable data may not exceed {} bytes but would become {0}", MAX_DATA)]
+    ///
+    /// The limit is [`MAX_DATA`] bytes, including any length prefix.
+    #[error("Variable data may not exceed {max} bytes but would become {0}", max = MAX_DATA)]
     TooLarge(usize),
     /// An illegal variable name was encountered.
     #[error("Variable name must consist only of uppercase A-Z, \u{03b8}, or after the first character 0-9")]
     InvalidName,
+    /// A file comment contained non-ASCII characters.
+    #[error("File comment must consist only of ASCII characters")]
+    InvalidComment,
+    /// The variable type can't be written to a variable file, because its format is unknown.
+    #[error("Variable type {0:?} is not supported in variable files")]
+    UnsupportedType(VariableType),
+    /// A variable was marked as archived, but its type can't be archived.
+    ///
+    /// See [`VariableType::is_archivable`].
+    #[error("Variables of type {0:?} cannot be archived")]
+    NotArchivable(VariableType),
+    /// An 11-byte variable header was requested, but it can't represent the variable's archive
+    /// flag or nonzero version.
+    #[error("A short variable header cannot record an archive flag or version")]
+    ShortHeader,
+    /// Typed data was written to a variable of a different type.
+    #[error("Cannot write {expected:?} data to a {actual:?} variable")]
+    WrongType {
+        /// The type the data is meant for.
+        expected: VariableType,
+        /// The type of the variable being written.
+        actual: VariableType,
+    },
+    /// Matrix dimensions were out of range or did not match the number of elements.
+    #[error("Matrix dimensions {rows}x{cols} are invalid for {len} elements")]
+    InvalidDimensions {
+        /// Number of rows requested.
+        rows: usize,
+        /// Number of columns requested.
+        cols: usize,
+        /// Number of elements provided.
+        len: usize,
+    },
+    /// A writer created with [`Writer::new_with_len`] was closed after writing a different amount
+    /// of data than was declared.
+    #[error(
+        "Variable was declared to contain {declared} bytes of data but {written} were written"
+    )]
+    LengthMismatch {
+        /// The length passed to `new_with_len`.
+        declared: u16,
+        /// The number of bytes actually written.
+        written: u16,
+    },
+    /// Data was written to a [`group::Writer`](crate::group::Writer) before any variable was
+    /// started.
+    #[error("start_var must be called on a group writer before data can be written")]
+    NoActiveVar,
+    /// A variable in a group had the wrong amount of data for its type, so readers would not be
+    /// able to tell where it ends.
+    ///
+    /// Reals and complex numbers must be exactly one value, and lists and matrices must contain
+    /// as many elements as their size says.
+    #[error("A group member of type {ty:?} cannot contain {len} bytes of data")]
+    InvalidMemberSize {
+        /// The type of the variable.
+        ty: VariableType,
+        /// The number of bytes of data written to it.
+        len: usize,
+    },
+}
+
+impl WriteError {
+    /// Return the `WriteError` contained in an IO error, if there is one.
+    pub fn from_io(e: &crate::io::Error) -> Option<&WriteError> {
+        e.get_ref()?.downcast_ref()
+    }
+}
+
+/// Errors encountered when creating a [`Writer`] with [`try_new`](Writer::try_new).
+///
+/// Unlike the errors returned by [`Writer::new`], invalid parameters can be matched directly:
+///
+/// ```
+/// # use tifiles::{VariableType, Writer, write::{CreateError, WriteError}};
+/// let err = Writer::try_new(tifiles::io::Cursor::new(vec![]), VariableType::Program, "bad", false)
+///     .err()
+///     .unwrap();
+/// assert!(matches!(err, CreateError::Invalid(WriteError::InvalidName)));
+/// ```
+#[derive(thiserror::Error, Debug)]
+pub enum CreateError {
+    /// The parameters of the variable are not valid.
+    #[error(transparent)]
+    Invalid(#[from] WriteError),
+    /// Writing the file header to the output failed.
+    #[error(transparent)]
+    Io(crate::io::Error),
+}
+
+impl From<crate::io::Error> for CreateError {
+    /// Extract a [`WriteError`] from an IO error if it contains one, otherwise wrap the IO
+    /// error in [`CreateError::Io`].
+    fn from(e: crate::io::Error) -> Self {
+        if WriteError::from_io(&e).is_none() {
+            return CreateError::Io(e);
+        }
+        let inner = e
+            .into_inner()
+            .and_then(|inner| inner.downcast().ok())
+            .expect("error was checked to contain a WriteError");
+        CreateError::Invalid(*inner)
+    }
+}
+
+/// The comment written to files if no other is specified.
+const DEFAULT_COMMENT: &str = "TI-8x variable writer by Peter Marheine";
+
+/// Verify the provided name is legal, truncate to the maximum length and translate θ to the
+/// θ token 0x5b (θ is the only non-ASCII character allowed, and is legal in any position).
+///
+/// The maximum length is 8 characters rather than 8 bytes of UTF-8, since every character
+/// including θ encodes to a single byte.
+pub(crate) fn encode_name(name: &str) -> Result<[u8; 8], WriteError> {
+    const THETA: char = '\u{03b8}';
+    let mut padded_name = [0u8; 8];
+    // i counts characters, which is also the offset of this character in the encoded name
+    for (i, c) in name.chars().enumerate().take(padded_name.len()) {
+        // Digits are allowed anywhere except the first character
+        let legal = c.is_ascii_uppercase() || c == THETA || (i > 0 && c.is_ascii_digit());
+        if !legal {
+            return Err(WriteError::InvalidName);
+        }
+        padded_name[i] = if c == THETA { 0x5b } else { c as u8 };
+    }
+    Ok(padded_name)
+}
+
+/// Encode a name as for [`encode_name`], returning errors as `io::Error`s.
+fn encode(name: &str) -> crate::io::Result<[u8; 8]> {
+    encode_name(name).map_err(crate::io::Error::other)
 }
 
 /// Writes TI variable files.
@@ -26,6 +181,11 @@ where
     w: ChecksumWriter<W>,
     data_bytes: u16,
     ty: VariableType,
+    /// Length of the variable header, either 11 or 13 bytes
+    header_len: u16,
+    /// The data length already written to the headers, including any length prefix, if it was
+    /// known when the writer was created
+    declared_len: Option<u16>,
 }
 
 impl<W: Write + Seek> Writer<W> {
@@ -36,56 +196,183 @@ impl<W: Write + Seek> Writer<W> {
     /// placement in archive on a calculator.
     ///
     /// If the given name is not legal for a calculator variable, this returns
-    /// [`WriteError::InvalidName`].
-    pub fn new(
-        mut output: W,
+    /// [`WriteError::InvalidName`]. If the variable type can't be stored in a variable file, this
+    /// returns [`WriteError::UnsupportedType`], and if `archived` is true for a type that can't
+    /// be archived this returns [`WriteError::NotArchivable`].
+    pub fn new(output: W, ty: VariableType, name: &str, archived: bool) -> crate::io::Result<Self> {
+        Self::with_comment(output, ty, name, archived, DEFAULT_COMMENT)
+    }
+
+    /// Open an output for writing, returning invalid parameters as a matchable error.
+    ///
+    /// This is the same as [`new`](Writer::new), except that the [`WriteError`]s it returns for
+    /// invalid parameters are returned directly in [`CreateError::Invalid`] instead of being
+    /// wrapped in an `io::Error`. Errors from the output are returned in [`CreateError::Io`].
+    pub fn try_new(
+        output: W,
         ty: VariableType,
         name: &str,
         archived: bool,
-    ) -> std::io::Result<Self> {
-        // Verify the provided name is legal, truncate to the maximum length and translate θ to the
-        // θ token (which is the only non-ASCII character allowed).
-        const THETA: char = '\u{03b8}';
-        let mut padded_name = [0u8; 8];
-        for (i, c) in name.chars().enumerate().take(padded_name.len()) {
-            if !c.is_ascii_uppercase() && c != THETA && (i == 0 && c.is_ascii_digit()) {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    WriteError::InvalidName,
-                ));
-            }
-            padded_name[i] = if c == THETA { 0x5b } else { c as u8 };
+    ) -> Result<Self, CreateError> {
+        Ok(Self::new(output, ty, name, archived)?)
+    }
+
+    /// Open an output for writing, with a custom file comment.
+    ///
+    /// This is the same as [`new`](Writer::new), but stores the provided comment in the file
+    /// rather than the default one. The comment is truncated or padded with spaces to exactly 42
+    /// bytes; it may be empty. To pad it with NULs instead, use
+    /// [`WriterBuilder::nul_padded_comment`].
+    ///
+    /// The comment must be ASCII; if it contains any other characters this returns
+    /// [`WriteError::InvalidComment`].
+    pub fn with_comment(
+        output: W,
+        ty: VariableType,
+        name: &str,
+        archived: bool,
+        comment: &str,
+    ) -> crate::io::Result<Self> {
+        let comment = pad_comment(comment, b' ')?;
+        Self::create(
+            output,
+            ty,
+            encode(name)?,
+            archived,
+            &comment,
+            0,
+            false,
+            None,
+        )
+    }
+
+    /// Open an output for writing a variable whose data length is known in advance.
+    ///
+    /// This is the same as [`new`](Writer::new), but the length fields are written with their
+    /// final values immediately so [`close`](Writer::close) only needs to append the checksum,
+    /// without seeking back to fill them in. `data_len` counts the variable data excluding any
+    /// length prefix, as [`Reader::len`](crate::Reader::len) does.
+    ///
+    /// If `data_len` is too large for a file this returns [`WriteError::TooLarge`], and if a
+    /// different amount of data has been written when the writer is closed, `close` returns
+    /// [`WriteError::LengthMismatch`].
+    pub fn new_with_len(
+        output: W,
+        ty: VariableType,
+        name: &str,
+        archived: bool,
+        data_len: u16,
+    ) -> crate::io::Result<Self> {
+        let comment = pad_comment(DEFAULT_COMMENT, b' ')?;
+        Self::create(
+            output,
+            ty,
+            encode(name)?,
+            archived,
+            &comment,
+            0,
+            false,
+            Some(data_len),
+        )
+    }
+
+    /// Open an output for writing, with a name that is not validated.
+    ///
+    /// This is the same as [`new`](Writer::new), except that `name` is stored in the file as-is
+    /// rather than being checked and encoded from a string. Calculators may not accept variables
+    /// with arbitrary names, but this allows names that [`new`](Writer::new) rejects, such as
+    /// the mixed-case names some programs use. Names shorter than 8 bytes should be padded with
+    /// NULs.
+    ///
+    /// This returns the same errors as `new`, except for [`WriteError::InvalidName`].
+    pub fn new_raw_name(
+        output: W,
+        ty: VariableType,
+        name: [u8; 8],
+        archived: bool,
+    ) -> crate::io::Result<Self> {
+        let comment = pad_comment(DEFAULT_COMMENT, b' ')?;
+        Self::create(output, ty, name, archived, &comment, 0, false, None)
+    }
+
+    /// Open an output for writing, with every header field specified.
+    ///
+    /// If `short_header` is true, the variable header is written in the 11-byte format without
+    /// version and flags, which requires that `archived` be false and `version` be 0. If
+    /// `data_len` is provided, the length fields are written for that much data rather than
+    /// being filled in on close.
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        mut output: W,
+        ty: VariableType,
+        padded_name: [u8; 8],
+        archived: bool,
+        comment: &[u8; 42],
+        version: u8,
+        short_header: bool,
+        data_len: Option<u16>,
+    ) -> crate::io::Result<Self> {
+        let has_length_prefix = ty
+            .has_length_prefix()
+            .ok_or(WriteError::UnsupportedType(ty))
+            .map_err(crate::io::Error::other)?;
+        if archived && !ty.is_archivable() {
+            return Err(crate::io::Error::other(WriteError::NotArchivable(ty)));
         }
+        if short_header && (archived || version != 0) {
+            return Err(crate::io::Error::other(WriteError::ShortHeader));
+        }
+        let header_len = if short_header { 11 } else { 13 };
+        let prefix_len = if has_length_prefix { 2 } else { 0 };
+        let declared_len = match data_len {
+            Some(len) => Some(
+                len.checked_add(prefix_len)
+                    .filter(|&l| l <= MAX_DATA)
+                    .ok_or_else(|| {
+                        crate::io::Error::other(WriteError::TooLarge(
+                            len as usize + prefix_len as usize,
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+        // Lengths are zero until filled in on close if the data length is unknown
+        let len_field = declared_len.unwrap_or(0);
+        let section_len = declared_len.map_or(0, |len| len + header_len + 4);
 
         // Constant header, comment, and 16-bit size of data section to follow
-        let header = b"\
-            **TI83F*\x1a\x0a\0\
-            TI-8x variable writer by Peter Marheine   \
-            \0\0\
-        ";
-        debug_assert_eq!(header.len(), 55);
-        output.write_all(header)?;
+        output.write_all(b"**TI83F*\x1a\x0a\0")?;
+        output.write_all(comment)?;
+        output.write_all(&section_len.to_le_bytes())?;
 
         // Subsequent data is largely covered by the file checksum
         let mut output = ChecksumWriter::new(output);
         output.enable_checksums(true);
 
         // Data section: variable header size, length of data, variable type
-        output.write_all(&[0xd, 0, 0, 0, ty as u8])?;
+        output.write_all(&header_len.to_le_bytes())?;
+        output.write_all(&len_field.to_le_bytes())?;
+        output.write_all(&[ty.to_byte()])?;
         // Name
         output.write_all(&padded_name)?;
-        // Version, flags, length of data again
-        output.write_all(&[0, if archived { 0x80 } else { 0 }, 0, 0])?;
+        // Version and flags, only in the longer header
+        if !short_header {
+            output.write_all(&[version, if archived { 0x80 } else { 0 }])?;
+        }
+        // Length of data again
+        output.write_all(&len_field.to_le_bytes())?;
 
         let mut out = Self {
             w: output,
             data_bytes: 0,
             ty,
+            header_len,
+            declared_len,
         };
-        if ty.has_length_prefix() {
+        if has_length_prefix {
             // Length prefix built into on actual data; counts against data length
             // in the data section header so writing it here to count against final data_bytes
-            out.write_all(&[0, 0])?;
+            out.write_all(&data_len.unwrap_or(0).to_le_bytes())?;
         }
 
         // Variable data follows, with 16-bit checksum at the end. Lengths are populated
@@ -93,61 +380,326 @@ impl<W: Write + Seek> Writer<W> {
         Ok(out)
     }
 
-    /// Finalize the variable file and return the underlying output.
+    /// Open an output for writing a copy of the variable in a [`Reader`](crate::Reader), with a
+    /// new name and archive flag.
     ///
-    /// This must be called in order to sync assorted internal data structures out to the file.
-    /// If this is not called the resulting file will appear to have no data and incorrect
-    /// checksums.
+    /// The type, version, file comment and all remaining data from `reader` are copied, so the
+    /// returned writer can be closed immediately (or have more data appended). The reader's
+    /// checksum is not checked; call [`Reader::verify`](crate::Reader::verify) afterwards if
+    /// needed. Comments that contain non-ASCII bytes (which can't be written) are replaced by the
+    /// default comment. Files with an 11-byte variable header are copied with the same header
+    /// unless the copy is archived, which requires the longer one.
     ///
-    /// The writer will be positioned after all file data on success.
-    pub fn close(self) -> std::io::Result<W> {
-        let Self {
-            mut w,
-            data_bytes,
-            ty,
-        } = self;
+    /// This returns the same errors as [`new`](Writer::new), or any error from reading.
+    pub fn copy_from<R: Read>(
+        output: W,
+        reader: &mut crate::Reader<R>,
+        name: &str,
+        archived: bool,
+    ) -> crate::io::Result<Self> {
+        let comment = core::str::from_utf8(reader.raw_comment())
+            .ok()
+            .filter(|c| c.is_ascii())
+            .unwrap_or(DEFAULT_COMMENT);
+        let mut out = Self::create(
+            output,
+            reader.ty(),
+            encode(name)?,
+            archived,
+            &pad_comment(comment, b' ')?,
+            reader.version(),
+            reader.entry_header_len() == 11 && !archived,
+            None,
+        )?;
+        out.write_from(reader)?;
+        Ok(out)
+    }
+
+    /// Write all of the data from a reader, returning the number of bytes copied.
+    ///
+    /// This is like [`io::copy`](std::io::copy), but stops once the file is full: if `src` has
+    /// more data than fits in [`MAX_DATA`] bytes, everything that fits is written and this
+    /// returns [`WriteError::TooLarge`]. At most one byte is read from `src` beyond what was
+    /// written, which is needed to tell that it had too much data.
+    pub fn write_from<R: Read>(&mut self, src: &mut R) -> crate::io::Result<u64> {
+        let mut buf = [0u8; 256];
+        let mut copied = 0u64;
+        loop {
+            let room = (MAX_DATA - self.data_bytes) as usize;
+            // Reading one byte more than fits detects oversized input without discarding data
+            let limit = buf.len().min(room + 1);
+            match src.read(&mut buf[..limit])? {
+                0 => return Ok(copied),
+                n => {
+                    let fits = n.min(room);
+                    self.write_all(&buf[..fits])?;
+                    copied += fits as u64;
+                    if n > fits {
+                        return Err(crate::io::Error::other(WriteError::TooLarge(
+                            self.data_bytes as usize + n - fits,
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the number of bytes of variable data written so far.
+    ///
+    /// For types with a length prefix this includes the two bytes of the prefix, since they count
+    /// towards the limit on data size: writes that would take this past the limit fail with
+    /// [`WriteError::TooLarge`].
+    pub fn data_len(&self) -> u16 {
+        self.data_bytes
+    }
+
+    /// Write the value of a [`Real`](VariableType::Real) variable.
+    ///
+    /// The value is encoded with [`encode_real`], so it is rounded to 14 significant digits.
+    pub fn write_real(&mut self, value: f64) -> crate::io::Result<()> {
+        let data = encode_real(value).map_err(crate::io::Error::other)?;
+        self.write_typed(VariableType::Real, &data)
+    }
+
+    /// Write the elements of a [`List`](VariableType::List) variable, preceded by their count.
+    pub fn write_list(&mut self, values: &[f64]) -> crate::io::Result<()> {
+        let mut data = Vec::with_capacity(2 + values.len() * crate::read::REAL_SIZE);
+        data.extend_from_slice(&(values.len() as u16).to_le_bytes());
+        encode_reals(&mut data, values)?;
+        self.write_typed(VariableType::List, &data)
+    }
+
+    /// Write the value of a [`Complex`](VariableType::Complex) variable.
+    ///
+    /// The parts are encoded with [`encode_complex`], so they are rounded to 14 significant
+    /// digits.
+    pub fn write_complex(&mut self, re: f64, im: f64) -> crate::io::Result<()> {
+        let data = encode_complex(re, im).map_err(crate::io::Error::other)?;
+        self.write_typed(VariableType::Complex, &data)
+    }
+
+    /// Write the elements of a [`ComplexList`](VariableType::ComplexList) variable, preceded by
+    /// their count.
+    ///
+    /// Each element is a pair of real and imaginary parts.
+    pub fn write_complex_list(&mut self, values: &[(f64, f64)]) -> crate::io::Result<()> {
+        let data = encode_complex_list(values).map_err(crate::io::Error::other)?;
+        self.write_typed(VariableType::ComplexList, &data)
+    }
 
+    /// Write the settings of a [`Window`](VariableType::Window) variable.
+    ///
+    /// The settings are encoded with [`encode_window`], so they are rounded to 14 significant
+    /// digits.
+    pub fn write_window(&mut self, window: &crate::read::WindowSettings) -> crate::io::Result<()> {
+        let data = encode_window(window).map_err(crate::io::Error::other)?;
+        self.write_typed(VariableType::Window, &data)
+    }
+
+    /// Write the settings of a [`TableSetup`](VariableType::TableSetup) variable.
+    ///
+    /// The settings are encoded with [`encode_table_setup`], so they are rounded to 14
+    /// significant digits.
+    pub fn write_table_setup(&mut self, setup: &crate::read::TableSetup) -> crate::io::Result<()> {
+        let data = encode_table_setup(setup).map_err(crate::io::Error::other)?;
+        self.write_typed(VariableType::TableSetup, &data)
+    }
+
+    /// Write the elements of a [`Matrix`](VariableType::Matrix) variable, preceded by its
+    /// dimensions.
+    ///
+    /// `values` are in row-major order, and there must be exactly `rows * cols` of them. Each
+    /// dimension must be at most 255, otherwise this returns [`WriteError::InvalidDimensions`].
+    /// Either dimension may be 0 for an empty matrix, which
+    /// [`decode_matrix`](crate::read::decode_matrix) also accepts.
+    pub fn write_matrix(
+        &mut self,
+        rows: usize,
+        cols: usize,
+        values: &[f64],
+    ) -> crate::io::Result<()> {
+        let dims_ok = rows <= 255 && cols <= 255;
+        if !dims_ok || rows * cols != values.len() {
+            return Err(crate::io::Error::other(WriteError::InvalidDimensions {
+                rows,
+                cols,
+                len: values.len(),
+            }));
+        }
+
+        let mut data = Vec::with_capacity(2 + values.len() * crate::read::REAL_SIZE);
+        data.extend_from_slice(&[cols as u8, rows as u8]);
+        encode_reals(&mut data, values)?;
+        self.write_typed(VariableType::Matrix, &data)
+    }
+
+    /// Write a complete piece of data for a variable of type `ty`, failing without writing
+    /// anything if it is for the wrong type or does not fit.
+    fn write_typed(&mut self, ty: VariableType, data: &[u8]) -> crate::io::Result<()> {
+        if self.ty != ty {
+            return Err(crate::io::Error::other(WriteError::WrongType {
+                expected: ty,
+                actual: self.ty,
+            }));
+        }
+        self.write_all(data)
+    }
+
+    /// Stop writing without finalizing the file, returning the underlying output.
+    ///
+    /// Unlike [`close`](Writer::close), this doesn't fill in the length fields or write the
+    /// checksum, so whatever has been written to the output is not a valid variable file: the
+    /// caller should discard or truncate it. The output is positioned after the last data
+    /// written.
+    pub fn abort(self) -> W {
+        self.w.w
+    }
+
+    /// Seek back from the end of `data_bytes` of data to fill in the length fields, then return
+    /// to the end of the data.
+    fn fill_lengths(
+        w: &mut ChecksumWriter<W>,
+        ty: VariableType,
+        header_len: u16,
+        data_bytes: u16,
+    ) -> crate::io::Result<()> {
         // Populate assorted length fields at offsets from file start:
-        // Length of data section overall (not covered by checksum)
+        // Length of data section overall (not covered by checksum), which is the data plus the
+        // variable header, its length field and the first data length
+        let section_overhead = header_len + 4;
         w.enable_checksums(false);
-        w.seek(SeekFrom::Current(-(data_bytes as i64) - 17 - 2))?;
-        w.write_all(&(data_bytes + 17).to_le_bytes())?;
+        w.seek(SeekFrom::Current(
+            -(data_bytes as i64) - section_overhead as i64 - 2,
+        ))?;
+        w.write_all(&(data_bytes + section_overhead).to_le_bytes())?;
         w.enable_checksums(true);
 
         // First length in data section
         w.seek(SeekFrom::Current(2))?;
         w.write_all(&data_bytes.to_le_bytes())?;
-        // Second length in data section
-        w.seek(SeekFrom::Current(11))?;
+        // Second length in data section, after the type, name and possibly version and flags
+        w.seek(SeekFrom::Current(header_len as i64 - 2))?;
         w.write_all(&data_bytes.to_le_bytes())?;
 
-        if ty.has_length_prefix() {
-            // Length embedded in data; data_bytes includes the zeroes already present
-            let embedded_len = (data_bytes - 2).to_le_bytes();
+        if ty.has_length_prefix() == Some(true) {
+            // Length embedded in data; data_bytes includes the zeroes already present, which
+            // were written on creation so an empty variable has exactly 2 bytes of data
+            let embedded_len = data_bytes
+                .checked_sub(2)
+                .expect("length prefix should be written when the writer is created")
+                .to_le_bytes();
             w.write_all(&embedded_len)?;
             w.seek(SeekFrom::Current(-2))?;
         }
 
         // Seek back to end of data section
         w.seek(SeekFrom::Current(data_bytes as i64))?;
+        Ok(())
+    }
+
+    /// Finalize the variable file and return the underlying output.
+    ///
+    /// This must be called in order to sync assorted internal data structures out to the file.
+    /// If this is not called the resulting file will appear to have no data and incorrect
+    /// checksums.
+    ///
+    /// The writer will be positioned after all file data on success.
+    pub fn close(self) -> crate::io::Result<W> {
+        let Self {
+            mut w,
+            data_bytes,
+            ty,
+            header_len,
+            declared_len,
+        } = self;
+
+        match declared_len {
+            None => Self::fill_lengths(&mut w, ty, header_len, data_bytes)?,
+            Some(declared) if declared != data_bytes => {
+                let prefix_len = if ty.has_length_prefix() == Some(true) {
+                    2
+                } else {
+                    0
+                };
+                return Err(crate::io::Error::other(WriteError::LengthMismatch {
+                    declared: declared - prefix_len,
+                    written: data_bytes.saturating_sub(prefix_len),
+                }));
+            }
+            // Lengths were written on creation, so only the checksum remains
+            Some(_) => {}
+        }
 
         // All data is written; just finish with the checksum
         let ChecksumWriter {
             mut w, checksum, ..
         } = w;
-        w.write_all(&checksum.to_le_bytes())?;
+        w.write_all(&checksum.finalize().to_le_bytes())?;
         Ok(w)
     }
 }
 
+impl<W: Read + Write + Seek> Writer<W> {
+    /// Open an existing variable file in order to append to its data.
+    ///
+    /// The file is read from the beginning of `rw` and validated as by
+    /// [`Reader::new`](crate::Reader::new), returning
+    /// [`ReadError::InvalidChecksum`](crate::read::ReadError::InvalidChecksum) if its checksum is
+    /// not valid. The returned writer is positioned at the end of the existing data, so writes
+    /// append to it and [`close`](Writer::close) updates the lengths and checksum to include
+    /// them. The type, name, comment and other header fields are unchanged.
+    ///
+    /// Anything in `rw` after the end of the file is left unchanged, except for any that is
+    /// overwritten by appended data.
+    pub fn open_existing(mut rw: W) -> crate::io::Result<Self> {
+        rw.seek(SeekFrom::Start(0))?;
+        let mut reader = crate::Reader::new(&mut rw)?;
+        let ty = reader.ty();
+        let header_len = reader.entry_header_len();
+        let data_bytes = reader.declared_data_len();
+
+        let mut buf = [0u8; 256];
+        while reader.read(&mut buf)? > 0 {}
+        let computed = reader.computed_checksum();
+        if let Err(e) = reader.finish()? {
+            return Err(crate::read::ReadError::InvalidChecksum {
+                computed: e.computed_checksum(),
+                read: e.read_checksum(),
+            }
+            .into());
+        }
+        // Appended data overwrites the stored checksum
+        rw.seek(SeekFrom::Current(-2))?;
+
+        // The running checksum of a new file doesn't include the lengths, since close adds
+        // them once they're known, so remove them from the checksum of the existing data.
+        let mut lengths = Checksum::new();
+        lengths.update(&data_bytes.to_le_bytes());
+        lengths.update(&data_bytes.to_le_bytes());
+        if ty.has_length_prefix() == Some(true) {
+            lengths.update(&(data_bytes - 2).to_le_bytes());
+        }
+        let mut w = ChecksumWriter::new(rw);
+        w.checksum = Checksum(computed.wrapping_sub(lengths.finalize()));
+        w.enable_checksums(true);
+
+        Ok(Writer {
+            w,
+            data_bytes,
+            ty,
+            header_len,
+            declared_len: None,
+        })
+    }
+}
+
 impl<W: Write + Seek> Write for Writer<W> {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
         // Verify total data size fits in 16-bit fields where it needs to go
         if (self.data_bytes as usize).saturating_add(buf.len()) > MAX_DATA as usize {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                WriteError::TooLarge(self.data_bytes as usize + buf.len()),
-            ));
+            return Err(crate::io::Error::other(WriteError::TooLarge(
+                self.data_bytes as usize + buf.len(),
+            )));
         }
 
         // Write data to backing writer
@@ -157,15 +709,251 @@ impl<W: Write + Seek> Write for Writer<W> {
         Ok(written)
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
+    fn flush(&mut self) -> crate::io::Result<()> {
         self.w.flush()
     }
 }
 
+/// Compute the checksum that a [`Writer`] stores in a file with the given contents.
+///
+/// The parameters are those of [`Writer::new`] followed by all of the data written to the
+/// writer, and this returns the value [`Writer::close`] writes at the end of the file for the
+/// same inputs, without producing the file. Returns the same errors as `Writer::new`, or
+/// [`WriteError::TooLarge`] if there is too much data for a file.
+pub fn data_checksum(
+    ty: VariableType,
+    name: &str,
+    archived: bool,
+    data: &[u8],
+) -> Result<u16, WriteError> {
+    let name = encode_name(name)?;
+    let has_length_prefix = ty
+        .has_length_prefix()
+        .ok_or(WriteError::UnsupportedType(ty))?;
+    if archived && !ty.is_archivable() {
+        return Err(WriteError::NotArchivable(ty));
+    }
+    let total_len = data.len() + if has_length_prefix { 2 } else { 0 };
+    if total_len > MAX_DATA as usize {
+        return Err(WriteError::TooLarge(total_len));
+    }
+    let total_len = (total_len as u16).to_le_bytes();
+
+    // The data section as Writer lays it out, without the variable data itself
+    let mut checksum = Checksum::new();
+    checksum.update(&[0xd, 0]);
+    checksum.update(&total_len);
+    checksum.update(&[ty.to_byte()]);
+    checksum.update(&name);
+    checksum.update(&[0, if archived { 0x80 } else { 0 }]);
+    checksum.update(&total_len);
+    if has_length_prefix {
+        checksum.update(&(data.len() as u16).to_le_bytes());
+    }
+    checksum.update(data);
+    Ok(checksum.finalize())
+}
+
+/// Append the encodings of `values` to `data`.
+fn encode_reals(data: &mut Vec<u8>, values: &[f64]) -> crate::io::Result<()> {
+    for &value in values {
+        data.extend_from_slice(&encode_real(value).map_err(crate::io::Error::other)?);
+    }
+    Ok(())
+}
+
+/// Pad or truncate a file comment to exactly 42 bytes, with `padding` filling unused bytes.
+///
+/// Returns [`WriteError::InvalidComment`] if the comment is not ASCII.
+fn pad_comment(comment: &str, padding: u8) -> crate::io::Result<[u8; 42]> {
+    if !comment.is_ascii() {
+        return Err(crate::io::Error::other(WriteError::InvalidComment));
+    }
+    let mut padded_comment = [padding; 42];
+    for (dst, &src) in padded_comment.iter_mut().zip(comment.as_bytes()) {
+        *dst = src;
+    }
+    Ok(padded_comment)
+}
+
+/// Collects the parameters for a [`Writer`].
+///
+/// Nothing is written to the output until [`build`](WriterBuilder::build) is called, so the
+/// name, archive flag and comment can all be changed freely until then.
+///
+/// ```
+/// # use tifiles::{VariableType, write::WriterBuilder};
+/// let writer = WriterBuilder::new(VariableType::AppVar, "NOTES")
+///     .archived(true)
+///     .comment("Written by my program")
+///     .build(tifiles::io::Cursor::new(Vec::new()))
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WriterBuilder {
+    ty: VariableType,
+    name: String,
+    archived: bool,
+    comment: String,
+    nul_padded_comment: bool,
+    version: u8,
+    short_header: bool,
+}
+
+impl WriterBuilder {
+    /// Begin building a writer for a variable with the given type and name.
+    ///
+    /// By default the variable is not archived, has version 0 and the file has the same comment
+    /// as files created with [`Writer::new`].
+    pub fn new(ty: VariableType, name: &str) -> Self {
+        WriterBuilder {
+            ty,
+            name: name.into(),
+            archived: false,
+            comment: DEFAULT_COMMENT.into(),
+            nul_padded_comment: false,
+            version: 0,
+            short_header: false,
+        }
+    }
+
+    /// Set the variable type.
+    pub fn ty(mut self, ty: VariableType) -> Self {
+        self.ty = ty;
+        self
+    }
+
+    /// Set the variable name.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set whether the variable should be archived.
+    pub fn archived(mut self, archived: bool) -> Self {
+        self.archived = archived;
+        self
+    }
+
+    /// Set the file comment, as for [`Writer::with_comment`].
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Set whether the file comment is padded with NULs rather than spaces.
+    ///
+    /// Some software pads comments with NULs, and some files have an empty comment consisting
+    /// only of NULs; combined with an empty [`comment`](WriterBuilder::comment) this can
+    /// reproduce such files exactly. Comments are padded with spaces by default.
+    pub fn nul_padded_comment(mut self, nul_padded: bool) -> Self {
+        self.nul_padded_comment = nul_padded;
+        self
+    }
+
+    /// Set the version of the variable's format, as returned by
+    /// [`Reader::version`](crate::Reader::version).
+    pub fn version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set whether to write the 11-byte variable header, which omits the version and flags.
+    ///
+    /// This makes files 2 bytes smaller and matches some older software, but can only be used
+    /// for variables that are not archived and have version 0:
+    /// [`build`](WriterBuilder::build) returns [`WriteError::ShortHeader`] otherwise. Files are
+    /// written with the 13-byte header by default.
+    pub fn short_header(mut self, short_header: bool) -> Self {
+        self.short_header = short_header;
+        self
+    }
+
+    /// Write the file header to `output` and return a writer for the variable data.
+    ///
+    /// Returns the same errors as [`Writer::with_comment`]; if the name or comment is invalid
+    /// nothing is written to the output.
+    pub fn build<W: Write + Seek>(&self, output: W) -> crate::io::Result<Writer<W>> {
+        Writer::create(
+            output,
+            self.ty,
+            encode(&self.name)?,
+            self.archived,
+            &pad_comment(
+                &self.comment,
+                if self.nul_padded_comment { 0 } else { b' ' },
+            )?,
+            self.version,
+            self.short_header,
+            None,
+        )
+    }
+}
+
+/// Writes TI variable files to outputs that can't seek.
+///
+/// This behaves the same as [`Writer`], but buffers the entire file in memory and writes it to
+/// the output in one pass when [`close`](BufferedWriter::close) is called. Because variables are
+/// limited to less than 64 KiB, the buffer is always fairly small.
+pub struct BufferedWriter<W>
+where
+    W: Write,
+{
+    w: Writer<Cursor<Vec<u8>>>,
+    output: W,
+}
+
+impl<W: Write> BufferedWriter<W> {
+    /// Open an output for writing.
+    ///
+    /// Parameters are the same as [`Writer::new`]. Nothing is written to the output until
+    /// the writer is closed.
+    pub fn new(output: W, ty: VariableType, name: &str, archived: bool) -> crate::io::Result<Self> {
+        Self::with_comment(output, ty, name, archived, DEFAULT_COMMENT)
+    }
+
+    /// Open an output for writing, with a custom file comment.
+    ///
+    /// Parameters are the same as [`Writer::with_comment`].
+    pub fn with_comment(
+        output: W,
+        ty: VariableType,
+        name: &str,
+        archived: bool,
+        comment: &str,
+    ) -> crate::io::Result<Self> {
+        Ok(BufferedWriter {
+            w: Writer::with_comment(Cursor::new(Vec::new()), ty, name, archived, comment)?,
+            output,
+        })
+    }
+
+    /// Finalize the variable file, write it to the output and return the output.
+    ///
+    /// If this is not called, nothing will be written to the output.
+    pub fn close(self) -> crate::io::Result<W> {
+        let Self { w, mut output } = self;
+        output.write_all(&w.close()?.into_inner())?;
+        Ok(output)
+    }
+}
+
+impl<W: Write> Write for BufferedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
+        self.w.write(buf)
+    }
+
+    fn flush(&mut self) -> crate::io::Result<()> {
+        // Data in the buffer isn't complete until the writer is closed, so there's nothing
+        // that can be flushed to the output.
+        Ok(())
+    }
+}
+
 /// Writes data to the backing object while computing a simple checksum.
 pub struct ChecksumWriter<W> {
     w: W,
-    checksum: u16,
+    checksum: Checksum,
     active: bool,
 }
 
@@ -179,7 +967,7 @@ impl<W> ChecksumWriter<W> {
     fn new(w: W) -> Self {
         ChecksumWriter {
             w,
-            checksum: 0,
+            checksum: Checksum::new(),
             active: false,
         }
     }
@@ -187,24 +975,22 @@ impl<W> ChecksumWriter<W> {
 
 /// Writes data to the backing `Write`r, updating the checksum if active.
 impl<W: Write> Write for ChecksumWriter<W> {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
         let written = self.w.write(buf)?;
         if self.active {
-            for &byte in &buf[..written] {
-                self.checksum = self.checksum.wrapping_add(byte as u16);
-            }
+            self.checksum.update(&buf[..written]);
         }
         Ok(written)
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
+    fn flush(&mut self) -> crate::io::Result<()> {
         self.w.flush()
     }
 }
 
 /// Seeks within the backing `Write`r, making no other changes.
 impl<W: Seek> Seek for ChecksumWriter<W> {
-    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+    fn seek(&mut self, pos: SeekFrom) -> crate::io::Result<u64> {
         self.w.seek(pos)
     }
 }
@@ -214,14 +1000,14 @@ impl<W: Seek> Seek for ChecksumWriter<W> {
 fn checksum_writer_works() {
     let mut writer = ChecksumWriter::new(Vec::<u8>::new());
 
-    writer.checksum = 0xFF00;
+    writer.checksum = Checksum(0xFF00);
     writer.enable_checksums(true);
     writer.write_all(&[255, 1, 0, 42]).unwrap();
-    assert_eq!(writer.checksum, 42);
+    assert_eq!(writer.checksum.finalize(), 42);
 
     writer.enable_checksums(false);
     writer.write_all(&[1, 2, 3, 4]).unwrap();
-    assert_eq!(writer.checksum, 42);
+    assert_eq!(writer.checksum.finalize(), 42);
 
     assert_eq!(writer.w, &[255, 1, 0, 42, 1, 2, 3, 4]);
 }
@@ -229,7 +1015,7 @@ fn checksum_writer_works() {
 /// A program file is written with exactly the correct data.
 #[test]
 fn empty_program_is_correct() {
-    use std::io::Cursor;
+    use crate::io::Cursor;
 
     let mut buf = Vec::<u8>::new();
     let writer = Writer::new(
@@ -252,3 +1038,726 @@ fn empty_program_is_correct() {
               \xd8\x00",
     );
 }
+
+/// A custom comment is padded to the full comment field length, and non-ASCII comments are
+/// rejected.
+#[test]
+fn custom_comment_is_padded() {
+    use crate::io::Cursor;
+
+    let mut buf = Vec::<u8>::new();
+    let writer = Writer::with_comment(
+        Cursor::new(&mut buf),
+        VariableType::AppVar,
+        "A",
+        false,
+        "Hello",
+    )
+    .unwrap();
+    writer.close().unwrap();
+    assert_eq!(
+        &buf[11..55],
+        b"Hello                                     \x13\0"
+    );
+
+    let err = Writer::with_comment(
+        Cursor::new(Vec::new()),
+        VariableType::AppVar,
+        "A",
+        false,
+        "Caf\u{e9}",
+    )
+    .err()
+    .expect("non-ASCII comment should be rejected");
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<WriteError>(),
+        Some(WriteError::InvalidComment)
+    ));
+}
+
+/// Empty comments are accepted, and comments can be padded with NULs instead of spaces.
+#[test]
+fn comments_can_be_nul_padded() {
+    use crate::io::Cursor;
+
+    let file = Writer::with_comment(
+        Cursor::new(Vec::new()),
+        VariableType::AppVar,
+        "A",
+        false,
+        "",
+    )
+    .unwrap()
+    .close()
+    .unwrap()
+    .into_inner();
+    assert_eq!(&file[11..53], &[b' '; 42]);
+
+    let file = WriterBuilder::new(VariableType::AppVar, "A")
+        .comment("")
+        .nul_padded_comment(true)
+        .build(Cursor::new(Vec::new()))
+        .unwrap()
+        .close()
+        .unwrap()
+        .into_inner();
+    assert_eq!(&file[11..53], &[0; 42]);
+    let reader = crate::Reader::new(Cursor::new(file)).unwrap();
+    assert_eq!(reader.raw_comment(), &[0; 42]);
+
+    let file = WriterBuilder::new(VariableType::AppVar, "A")
+        .comment("Hello")
+        .nul_padded_comment(true)
+        .build(Cursor::new(Vec::new()))
+        .unwrap()
+        .close()
+        .unwrap()
+        .into_inner();
+    assert_eq!(&file[11..16], b"Hello");
+    assert_eq!(&file[16..53], &[0; 37]);
+}
+
+/// Names are accepted or rejected according to calculator rules.
+#[test]
+fn name_validation() {
+    assert_eq!(encode_name("STR7").unwrap(), *b"STR7\0\0\0\0");
+    assert_eq!(encode_name("A1B2").unwrap(), *b"A1B2\0\0\0\0");
+    assert!(matches!(encode_name("abc"), Err(WriteError::InvalidName)));
+    assert!(matches!(encode_name("Abc"), Err(WriteError::InvalidName)));
+    assert!(matches!(encode_name("1ABC"), Err(WriteError::InvalidName)));
+    assert!(matches!(encode_name("A B"), Err(WriteError::InvalidName)));
+    // Names are truncated to 8 characters
+    assert_eq!(encode_name("ABCDEFGHIJ").unwrap(), *b"ABCDEFGH");
+
+    let err = Writer::new(
+        crate::io::Cursor::new(Vec::new()),
+        VariableType::Program,
+        "abc",
+        false,
+    )
+    .err()
+    .expect("lowercase name should be rejected");
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<WriteError>(),
+        Some(WriteError::InvalidName)
+    ));
+}
+
+#[test]
+fn rejects_archiving_system_vars() {
+    let err = Writer::new(Cursor::new(Vec::new()), VariableType::Window, "A", true)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<WriteError>(),
+        Some(WriteError::NotArchivable(VariableType::Window))
+    ));
+    assert!(Writer::new(Cursor::new(Vec::new()), VariableType::Window, "A", false).is_ok());
+}
+
+#[test]
+fn theta_names() {
+    assert_eq!(encode_name("θ").unwrap(), *b"\x5b\0\0\0\0\0\0\0");
+    assert_eq!(encode_name("θ1").unwrap(), *b"\x5b1\0\0\0\0\0\0");
+    assert_eq!(encode_name("Aθ2").unwrap(), *b"A\x5b2\0\0\0\0\0");
+    // θ is two bytes of UTF-8 but one character, so this is 8 characters long
+    assert_eq!(encode_name("θθθθθθθθθ").unwrap(), [0x5b; 8]);
+    assert_eq!(encode_name("ABCDEFGθ9").unwrap(), *b"ABCDEFG\x5b");
+    assert!(matches!(encode_name("A θ"), Err(WriteError::InvalidName)));
+    assert!(matches!(encode_name("1θ"), Err(WriteError::InvalidName)));
+}
+
+/// A buffered writer emits exactly the same data as a seeking one.
+#[test]
+fn buffered_writer_matches_writer() {
+    let mut expected = Vec::<u8>::new();
+    let mut writer = Writer::new(
+        Cursor::new(&mut expected),
+        VariableType::AppVar,
+        "BUF",
+        false,
+    )
+    .unwrap();
+    writer.write_all(b"some data").unwrap();
+    writer.close().unwrap();
+
+    // &mut Vec is Write but not Seek
+    let mut actual = Vec::<u8>::new();
+    let mut writer = BufferedWriter::new(&mut actual, VariableType::AppVar, "BUF", false).unwrap();
+    writer.write_all(b"some data").unwrap();
+    writer.close().unwrap();
+
+    assert_eq!(actual, expected);
+}
+
+/// A builder writes nothing if its parameters are invalid, and the same data as a writer
+/// otherwise.
+#[test]
+fn builder_matches_writer() {
+    let mut expected = Vec::<u8>::new();
+    let writer = Writer::with_comment(
+        Cursor::new(&mut expected),
+        VariableType::Program,
+        "PRGM",
+        true,
+        "A comment",
+    )
+    .unwrap();
+    writer.close().unwrap();
+
+    let builder = WriterBuilder::new(VariableType::Program, "bad name");
+    let mut actual = Vec::<u8>::new();
+    assert!(builder.build(Cursor::new(&mut actual)).is_err());
+    assert!(actual.is_empty());
+
+    builder
+        .name("PRGM")
+        .archived(true)
+        .comment("A comment")
+        .build(Cursor::new(&mut actual))
+        .unwrap()
+        .close()
+        .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn writes_empty_length_prefixed_vars() {
+    fn empty(ty: VariableType) -> Vec<u8> {
+        let comment = "Created by SourceCoder 3 - sc.cemetech.net";
+        Writer::with_comment(Cursor::new(Vec::new()), ty, "A", false, comment)
+            .unwrap()
+            .close()
+            .unwrap()
+            .into_inner()
+    }
+
+    // Identical to the file in read::reads_empty_appvar
+    assert_eq!(
+        empty(VariableType::AppVar),
+        b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+          \x13\0\x0d\0\x02\0\x15A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x67\0"
+    );
+    assert_eq!(
+        empty(VariableType::Program),
+        b"**TI83F*\x1a\x0a\0Created by SourceCoder 3 - sc.cemetech.net\
+          \x13\0\x0d\0\x02\0\x05A\0\0\0\0\0\0\0\0\0\x02\0\0\0\x57\0"
+    );
+}
+
+#[test]
+fn try_new_returns_typed_errors() {
+    assert!(matches!(
+        Writer::try_new(Cursor::new(Vec::new()), VariableType::Program, "a", false),
+        Err(CreateError::Invalid(WriteError::InvalidName))
+    ));
+    assert!(matches!(
+        Writer::try_new(Cursor::new(Vec::new()), VariableType::Equation, "A", true),
+        Err(CreateError::Invalid(WriteError::NotArchivable(
+            VariableType::Equation
+        )))
+    ));
+
+    #[cfg(feature = "std")]
+    {
+        let mut full = [0u8; 10];
+        let err = Writer::try_new(
+            Cursor::new(&mut full[..]),
+            VariableType::Program,
+            "A",
+            false,
+        )
+        .err()
+        .unwrap();
+        match err {
+            CreateError::Io(e) => assert_eq!(e.kind(), crate::io::ErrorKind::WriteZero),
+            other => panic!("expected an IO error, got {:?}", other),
+        }
+    }
+
+    assert!(Writer::try_new(Cursor::new(Vec::new()), VariableType::Program, "A", false).is_ok());
+}
+
+#[test]
+fn appends_to_existing_files() {
+    let mut w = WriterBuilder::new(VariableType::AppVar, "NOTES")
+        .short_header(true)
+        .build(Cursor::new(Vec::new()))
+        .unwrap();
+    w.write_all(b"first").unwrap();
+    let mut file = w.close().unwrap();
+    // Trailing data is kept
+    file.get_mut().extend_from_slice(b"0123456789");
+
+    let mut w = Writer::open_existing(file).unwrap();
+    assert_eq!(w.data_len(), 7);
+    w.write_all(b", second").unwrap();
+    let file = w.close().unwrap().into_inner();
+
+    let mut expected = WriterBuilder::new(VariableType::AppVar, "NOTES")
+        .short_header(true)
+        .build(Cursor::new(Vec::new()))
+        .unwrap();
+    expected.write_all(b"first, second").unwrap();
+    let expected = expected.close().unwrap().into_inner();
+    assert_eq!(&file[..expected.len()], expected);
+    assert_eq!(&file[expected.len()..], b"89");
+
+    // Types without a length prefix, and corrupt files
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Real, "A", false).unwrap();
+    w.write_all(&[0; 4]).unwrap();
+    let mut file = w.close().unwrap();
+    let mut w = Writer::open_existing(&mut file).unwrap();
+    w.write_all(&[0; 5]).unwrap();
+    w.close().unwrap();
+    let mut reader = crate::Reader::new(&file.get_ref()[..]).unwrap();
+    assert_eq!(reader.len(), 9);
+    assert!(reader.verify().unwrap());
+
+    file.get_mut()[60] = b'B';
+    let e = Writer::open_existing(&mut file).err().unwrap();
+    assert!(matches!(
+        e.get_ref().and_then(|e| e.downcast_ref()),
+        Some(crate::read::ReadError::InvalidChecksum { .. })
+    ));
+}
+
+#[test]
+fn data_len_counts_writes() {
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::AppVar, "A", false).unwrap();
+    assert_eq!(w.data_len(), 2);
+    w.write_all(b"hello").unwrap();
+    assert_eq!(w.data_len(), 7);
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Real, "A", false).unwrap();
+    assert_eq!(w.data_len(), 0);
+    w.write_real(1.0).unwrap();
+    assert_eq!(w.data_len(), 9);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn writes_from_readers() {
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::AppVar, "A", false).unwrap();
+    assert_eq!(w.write_from(&mut &b"hello"[..]).unwrap(), 5);
+    assert_eq!(w.write_from(&mut std::io::empty()).unwrap(), 0);
+    assert_eq!(w.data_len(), 7);
+    let data = w.close().unwrap().into_inner();
+    let mut reader = crate::Reader::new(&data[..]).unwrap();
+    assert_eq!(reader.read_all_data().unwrap(), b"hello");
+
+    // The length prefix counts towards the limit
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::AppVar, "A", false).unwrap();
+    let mut src = std::io::repeat(0).take(MAX_DATA as u64);
+    let e = w.write_from(&mut src).unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&e),
+        Some(WriteError::TooLarge(_))
+    ));
+    assert_eq!(w.data_len(), MAX_DATA);
+    // Of the two bytes that don't fit, only one was read
+    assert_eq!(src.limit(), 1);
+
+    let mut src = std::io::repeat(0).take(MAX_DATA as u64 + 100);
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Real, "A", false).unwrap();
+    let e = w.write_from(&mut src).unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&e),
+        Some(WriteError::TooLarge(65519))
+    ));
+    assert_eq!(w.data_len(), MAX_DATA);
+    assert_eq!(src.limit(), 99);
+}
+
+#[test]
+fn writes_maximum_data() {
+    use crate::io::Write;
+
+    let data = (0..MAX_DATA).map(|i| i as u8).collect::<Vec<_>>();
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Real, "A", false).unwrap();
+    w.write_all(&data).unwrap();
+    assert_eq!(w.data_len(), MAX_DATA);
+    // Nothing more can be written, not even a single byte
+    let e = w.write(&[0]).unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&e),
+        Some(WriteError::TooLarge(65519))
+    ));
+    let file = w.close().unwrap().into_inner();
+    assert_eq!(
+        file.len(),
+        MAX_DATA as usize + VariableType::Real.data_overhead().unwrap()
+    );
+
+    let mut reader = crate::Reader::new(&file[..]).unwrap();
+    assert_eq!(reader.data_section_len(), u16::MAX);
+    assert_eq!(reader.len(), MAX_DATA);
+    assert_eq!(reader.read_all_data().unwrap(), data);
+    reader.finish().unwrap().expect("checksum should be valid");
+
+    // The length prefix counts towards the limit
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::AppVar, "A", false).unwrap();
+    w.write_all(&data[2..]).unwrap();
+    let e = w.write(&[0]).unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&e),
+        Some(WriteError::TooLarge(65519))
+    ));
+    let file = w.close().unwrap().into_inner();
+    let mut reader = crate::Reader::new(&file[..]).unwrap();
+    assert_eq!(reader.len(), MAX_DATA - 2);
+    assert_eq!(reader.embedded_len(), Some(MAX_DATA - 2));
+    assert_eq!(reader.read_all_data().unwrap(), data[2..]);
+    reader.finish().unwrap().expect("checksum should be valid");
+}
+
+#[test]
+fn version_round_trips() {
+    let data = WriterBuilder::new(VariableType::Program, "A")
+        .version(6)
+        .build(Cursor::new(Vec::new()))
+        .unwrap()
+        .close()
+        .unwrap()
+        .into_inner();
+    let mut reader = crate::Reader::new(&data[..]).unwrap();
+    assert_eq!(reader.version(), 6);
+
+    let copy = Writer::copy_from(Cursor::new(Vec::new()), &mut reader, "B", false)
+        .unwrap()
+        .close()
+        .unwrap()
+        .into_inner();
+    assert_eq!(crate::Reader::new(&copy[..]).unwrap().version(), 6);
+}
+
+#[test]
+fn copies_variables() {
+    let mut original = Writer::with_comment(
+        Cursor::new(Vec::new()),
+        VariableType::ProtectedProgram,
+        "OLD",
+        false,
+        "Original comment",
+    )
+    .unwrap();
+    original.write_all(&[0xbb, 0x6d, 0xc9]).unwrap();
+    let original = original.close().unwrap().into_inner();
+
+    let mut reader = crate::Reader::new(&original[..]).unwrap();
+    let copy = Writer::copy_from(Cursor::new(Vec::new()), &mut reader, "NEW", true)
+        .unwrap()
+        .close()
+        .unwrap()
+        .into_inner();
+    assert!(reader.verify().unwrap());
+
+    let mut reader = crate::Reader::new(&copy[..]).unwrap();
+    assert_eq!(reader.ty(), VariableType::ProtectedProgram);
+    assert_eq!(reader.name_str(), "NEW");
+    assert!(reader.is_archived());
+    assert_eq!(reader.comment_str(), "Original comment");
+    let mut data = vec![];
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, [0xbb, 0x6d, 0xc9]);
+    assert!(reader.verify().unwrap());
+}
+
+#[test]
+fn typed_data_round_trips() {
+    use crate::io::Read;
+    use crate::read::{
+        decode_list, decode_matrix, decode_real, decode_table_setup, decode_window, Reader,
+        TableSetup,
+    };
+
+    fn read_data(file: Vec<u8>) -> Vec<u8> {
+        let mut data = Vec::new();
+        Reader::new(Cursor::new(file))
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        data
+    }
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Real, "A", false).unwrap();
+    w.write_real(-2.5).unwrap();
+    let data = read_data(w.close().unwrap().into_inner());
+    assert_eq!(decode_real(&data), Ok(-2.5));
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::List, "L1", false).unwrap();
+    w.write_list(&[1.0, 2.0, 3.0]).unwrap();
+    let data = read_data(w.close().unwrap().into_inner());
+    assert_eq!(decode_list(&data), Ok(vec![1.0, 2.0, 3.0]));
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Matrix, "A", false).unwrap();
+    w.write_matrix(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0])
+        .unwrap();
+    let data = read_data(w.close().unwrap().into_inner());
+    let matrix = decode_matrix(&data).unwrap();
+    assert_eq!((matrix.rows(), matrix.cols()), (2, 3));
+    assert_eq!(matrix.get(0, 2), Some(3.0));
+    assert_eq!(matrix.get(1, 0), Some(4.0));
+
+    for (rows, cols) in [(0, 0), (0, 3), (3, 0)] {
+        let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Matrix, "A", false).unwrap();
+        w.write_matrix(rows, cols, &[]).unwrap();
+        let data = read_data(w.close().unwrap().into_inner());
+        let matrix = decode_matrix(&data).unwrap();
+        assert_eq!((matrix.rows(), matrix.cols()), (rows, cols));
+    }
+
+    let mut window = decode_window(&[0; crate::read::WINDOW_SIZE]).unwrap();
+    window.x_min = -4.7;
+    window.x_max = 4.7;
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Window, "A", false).unwrap();
+    w.write_window(&window).unwrap();
+    let data = read_data(w.close().unwrap().into_inner());
+    assert_eq!(decode_window(&data), Ok(window));
+
+    let setup = TableSetup {
+        tbl_start: 0.0,
+        delta_tbl: 0.1,
+        flags: 0,
+    };
+    let mut w = Writer::new(
+        Cursor::new(Vec::new()),
+        VariableType::TableSetup,
+        "A",
+        false,
+    )
+    .unwrap();
+    w.write_table_setup(&setup).unwrap();
+    let data = read_data(w.close().unwrap().into_inner());
+    assert_eq!(decode_table_setup(&data), Ok(setup));
+}
+
+#[test]
+fn typed_data_is_checked() {
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Program, "A", false).unwrap();
+    let err = w.write_matrix(1, 1, &[1.0]).unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<WriteError>(),
+        Some(WriteError::WrongType {
+            expected: VariableType::Matrix,
+            actual: VariableType::Program,
+        })
+    ));
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::Matrix, "A", false).unwrap();
+    let err = w.write_matrix(2, 2, &[1.0]).unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<WriteError>(),
+        Some(WriteError::InvalidDimensions { .. })
+    ));
+    let err = w.write_matrix(256, 0, &[]).unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<WriteError>(),
+        Some(WriteError::InvalidDimensions { .. })
+    ));
+
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::List, "L1", false).unwrap();
+    let err = w.write_list(&[0.0; 7500]).unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<WriteError>(),
+        Some(WriteError::TooLarge(67502))
+    ));
+}
+
+#[test]
+fn write_errors_are_extracted() {
+    let err = Writer::new(Cursor::new(Vec::new()), VariableType::Window, "A", true)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        WriteError::from_io(&err),
+        Some(WriteError::NotArchivable(VariableType::Window))
+    ));
+
+    let other = crate::io::Error::other("not a write error");
+    assert!(WriteError::from_io(&other).is_none());
+    let simple = crate::io::Error::from(crate::io::ErrorKind::UnexpectedEof);
+    assert!(WriteError::from_io(&simple).is_none());
+}
+
+#[test]
+fn writes_short_headers() {
+    use crate::io::Read;
+
+    let mut w = WriterBuilder::new(VariableType::Program, "SHORT")
+        .short_header(true)
+        .build(Cursor::new(Vec::new()))
+        .unwrap();
+    w.write_all(b"\xde\x2a").unwrap();
+    let data = w.close().unwrap().into_inner();
+    assert_eq!(data.len(), 55 + 2 + 11 + 2 + 4 + 2);
+    assert_eq!(&data[53..57], b"\x13\0\x0b\0");
+
+    let mut reader = crate::Reader::new(&data[..]).unwrap();
+    assert_eq!(reader.entry_header_len(), 11);
+    assert_eq!(reader.name_str(), "SHORT");
+    assert_eq!(reader.len(), 2);
+    let mut contents = vec![];
+    reader.read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, b"\xde\x2a");
+    assert!(reader.verify().unwrap());
+
+    // Copies keep the short header
+    let mut reader = crate::Reader::new(&data[..]).unwrap();
+    let copy = Writer::copy_from(Cursor::new(Vec::new()), &mut reader, "COPY", false)
+        .unwrap()
+        .close()
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        crate::Reader::new(&copy[..]).unwrap().entry_header_len(),
+        11
+    );
+
+    for builder in [
+        WriterBuilder::new(VariableType::Program, "A").archived(true),
+        WriterBuilder::new(VariableType::Program, "A").version(1),
+    ] {
+        let err = builder
+            .short_header(true)
+            .build(Cursor::new(Vec::new()))
+            .err()
+            .unwrap();
+        assert!(matches!(
+            WriteError::from_io(&err),
+            Some(WriteError::ShortHeader)
+        ));
+    }
+}
+
+#[test]
+fn data_checksum_matches_writer() {
+    for (ty, name, archived, data) in [
+        (VariableType::Program, "A", false, &b""[..]),
+        (VariableType::AppVar, "DATA", true, &[0xff; 300][..]),
+        (
+            VariableType::Real,
+            "\u{03b8}",
+            false,
+            &[0, 0x80, 0x10, 0, 0, 0, 0, 0, 0][..],
+        ),
+    ] {
+        let mut w = Writer::new(Cursor::new(Vec::new()), ty, name, archived).unwrap();
+        w.write_all(data).unwrap();
+        let file = w.close().unwrap().into_inner();
+        let stored = u16::from_le_bytes([file[file.len() - 2], file[file.len() - 1]]);
+        assert_eq!(data_checksum(ty, name, archived, data).unwrap(), stored);
+    }
+
+    assert!(matches!(
+        data_checksum(VariableType::Program, "bad", false, b""),
+        Err(WriteError::InvalidName)
+    ));
+    assert!(matches!(
+        data_checksum(VariableType::AppVar, "A", false, &[0; 65518]),
+        Err(WriteError::TooLarge(65520))
+    ));
+}
+
+#[test]
+fn raw_names_are_not_validated() {
+    let file = Writer::new_raw_name(
+        Cursor::new(Vec::new()),
+        VariableType::Program,
+        *b"Mixed\0\0\0",
+        false,
+    )
+    .unwrap()
+    .close()
+    .unwrap()
+    .into_inner();
+    let reader = crate::Reader::new(&file[..]).unwrap();
+    assert_eq!(reader.name(), b"Mixed\0\0\0");
+    assert_eq!(reader.name_str(), "Mixed");
+
+    // Other parameters are still checked
+    let err = Writer::new_raw_name(Cursor::new(Vec::new()), VariableType::Window, [0; 8], true)
+        .err()
+        .unwrap();
+    assert!(matches!(
+        WriteError::from_io(&err),
+        Some(WriteError::NotArchivable(VariableType::Window))
+    ));
+}
+
+#[test]
+fn aborted_files_are_incomplete() {
+    let mut w = Writer::new(Cursor::new(Vec::new()), VariableType::AppVar, "A", false).unwrap();
+    w.write_all(b"partial").unwrap();
+    let mut output = w.abort();
+    assert_eq!(output.position(), 74 + 7);
+
+    let data = output.get_mut();
+    // Lengths are still zero, and there's no checksum
+    assert_eq!(&data[53..55], b"\0\0");
+    assert_eq!(data.len(), 81);
+    assert!(crate::Reader::new(&data[..]).is_err());
+}
+
+#[test]
+fn known_lengths_are_written_without_seeking() {
+    use crate::io::{Cursor, Seek, SeekFrom, Write};
+
+    /// Fails the test if the writer seeks at all.
+    #[derive(Debug)]
+    struct NoSeek(Vec<u8>);
+    impl Write for NoSeek {
+        fn write(&mut self, buf: &[u8]) -> crate::io::Result<usize> {
+            self.0.write(buf)
+        }
+        fn flush(&mut self) -> crate::io::Result<()> {
+            Ok(())
+        }
+    }
+    impl Seek for NoSeek {
+        fn seek(&mut self, pos: SeekFrom) -> crate::io::Result<u64> {
+            panic!("writer seeked to {:?}", pos);
+        }
+    }
+
+    for (ty, data) in [
+        (VariableType::Program, &b"\xde\x2aHI\x2a"[..]),
+        (VariableType::Real, b"\x00\x80\x10\0\0\0\0\0\0"),
+        (VariableType::AppVar, b""),
+    ] {
+        let mut w = Writer::new(Cursor::new(Vec::new()), ty, "A", false).unwrap();
+        w.write_all(data).unwrap();
+        let expected = w.close().unwrap().into_inner();
+
+        let mut w =
+            Writer::new_with_len(NoSeek(Vec::new()), ty, "A", false, data.len() as u16).unwrap();
+        w.write_all(data).unwrap();
+        assert_eq!(w.close().unwrap().0, expected);
+    }
+
+    let mut w =
+        Writer::new_with_len(NoSeek(Vec::new()), VariableType::AppVar, "A", false, 4).unwrap();
+    w.write_all(b"abc").unwrap();
+    let err = w.close().unwrap_err();
+    assert!(matches!(
+        WriteError::from_io(&err),
+        Some(WriteError::LengthMismatch {
+            declared: 4,
+            written: 3
+        })
+    ));
+
+    let err = Writer::new_with_len(
+        NoSeek(Vec::new()),
+        VariableType::AppVar,
+        "A",
+        false,
+        MAX_DATA,
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(
+        WriteError::from_io(&err),
+        Some(WriteError::TooLarge(65520))
+    ));
+}
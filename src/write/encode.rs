@@ -0,0 +1,271 @@
+//! Encoders for the structured data contained in some variable types.
+
+use alloc::format;
+#[cfg(test)]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::read::{
+    TableSetup, WindowSettings, COMPLEX_FLAG, COMPLEX_SIZE, REAL_SIZE, TABLE_SETUP_SIZE,
+    WINDOW_SIZE,
+};
+
+/// Errors encountered when encoding variable data.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum EncodeError {
+    /// The value cannot be represented, because its magnitude is too large or it is not finite.
+    #[error("{0} cannot be represented as a TI real number")]
+    OutOfRange(f64),
+    /// A list has more elements than its 16-bit count can represent.
+    #[error("{0} elements is too many for a list")]
+    TooManyElements(usize),
+}
+
+/// Encode a real number in the 9-byte TI floating-point format.
+///
+/// This is the inverse of [`decode_real`](crate::read::decode_real). TI reals have 14 significant
+/// decimal digits, so values are rounded to 14 significant digits (with ties rounded to even).
+/// Values whose magnitude is too small to represent (less than 1ᴇ-99) are encoded as zero.
+///
+/// Returns [`EncodeError::OutOfRange`] if the value is not finite or would round to a magnitude
+/// of 1ᴇ100 or greater.
+pub fn encode_real(value: f64) -> Result<[u8; REAL_SIZE], EncodeError> {
+    if !value.is_finite() {
+        return Err(EncodeError::OutOfRange(value));
+    }
+
+    let mut out = [0u8; REAL_SIZE];
+    out[1] = 0x80;
+    if value == 0.0 {
+        return Ok(out);
+    }
+
+    // Formatting gives us correctly-rounded decimal digits and an exponent, like "-1.2345e-5"
+    let s = format!("{:.13e}", value);
+    let (mantissa, exponent) = s.split_once('e').expect("formatted float has an exponent");
+    let exponent: i32 = exponent.parse().expect("exponent is an integer");
+    if exponent > 99 {
+        return Err(EncodeError::OutOfRange(value));
+    }
+    if exponent < -99 {
+        return Ok(out);
+    }
+
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => (true, m),
+        None => (false, mantissa),
+    };
+    let mut digits = mantissa
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|d| d - b'0');
+    for byte in &mut out[2..] {
+        let hi = digits.next().unwrap_or(0);
+        let lo = digits.next().unwrap_or(0);
+        *byte = (hi << 4) | lo;
+    }
+    if negative {
+        out[0] = 0x80;
+    }
+    out[1] = (0x80 + exponent) as u8;
+
+    Ok(out)
+}
+
+/// Encode a complex number in the 18-byte TI format.
+///
+/// This is the inverse of [`decode_complex`](crate::read::decode_complex). Each part is encoded as
+/// with [`encode_real`], then marked as belonging to a complex number by setting the object type
+/// in its flags byte to 0x0C; without that type calculators would interpret the data as two reals.
+pub fn encode_complex(re: f64, im: f64) -> Result<[u8; COMPLEX_SIZE], EncodeError> {
+    let mut out = [0u8; COMPLEX_SIZE];
+    let (re_out, im_out) = out.split_at_mut(REAL_SIZE);
+    for (part, value) in [(re_out, re), (im_out, im)] {
+        part.copy_from_slice(&encode_real(value)?);
+        part[0] |= COMPLEX_FLAG;
+    }
+    Ok(out)
+}
+
+/// Encode a list of complex numbers, as the data of a
+/// [`ComplexList`](crate::VariableType::ComplexList) variable.
+///
+/// This is the inverse of [`decode_complex_list`](crate::read::decode_complex_list): a 16-bit
+/// element count followed by each element encoded with [`encode_complex`]. Returns
+/// [`EncodeError::TooManyElements`] if there are more than 65535 elements.
+pub fn encode_complex_list(values: &[(f64, f64)]) -> Result<Vec<u8>, EncodeError> {
+    let count =
+        u16::try_from(values.len()).map_err(|_| EncodeError::TooManyElements(values.len()))?;
+    let mut out = Vec::with_capacity(2 + values.len() * COMPLEX_SIZE);
+    out.extend_from_slice(&count.to_le_bytes());
+    for &(re, im) in values {
+        out.extend_from_slice(&encode_complex(re, im)?);
+    }
+    Ok(out)
+}
+
+/// Encode graph window settings, as the data of a [`Window`](crate::VariableType::Window)
+/// variable.
+///
+/// This is the inverse of [`decode_window`](crate::read::decode_window): each setting is encoded
+/// with [`encode_real`], so values are rounded to 14 significant digits.
+pub fn encode_window(window: &WindowSettings) -> Result<[u8; WINDOW_SIZE], EncodeError> {
+    let mut out = [0u8; WINDOW_SIZE];
+    out[0] = window.leading_byte;
+    for (part, value) in out[1..].chunks_exact_mut(REAL_SIZE).zip(window.values()) {
+        part.copy_from_slice(&encode_real(value)?);
+    }
+    Ok(out)
+}
+
+/// Encode table settings, as the data of a [`TableSetup`](crate::VariableType::TableSetup)
+/// variable.
+///
+/// This is the inverse of [`decode_table_setup`](crate::read::decode_table_setup): TblStart and
+/// ΔTbl encoded with [`encode_real`], followed by the flags byte.
+pub fn encode_table_setup(setup: &TableSetup) -> Result<[u8; TABLE_SETUP_SIZE], EncodeError> {
+    let mut out = [0u8; TABLE_SETUP_SIZE];
+    out[..REAL_SIZE].copy_from_slice(&encode_real(setup.tbl_start)?);
+    out[REAL_SIZE..2 * REAL_SIZE].copy_from_slice(&encode_real(setup.delta_tbl)?);
+    out[2 * REAL_SIZE] = setup.flags;
+    Ok(out)
+}
+
+#[test]
+fn encodes_reals() {
+    assert_eq!(encode_real(0.0), Ok(*b"\x00\x80\0\0\0\0\0\0\0"));
+    assert_eq!(encode_real(-0.0), Ok(*b"\x00\x80\0\0\0\0\0\0\0"));
+    assert_eq!(encode_real(-125.0), Ok(*b"\x80\x82\x12\x50\0\0\0\0\0"));
+    assert_eq!(encode_real(0.0025), Ok(*b"\x00\x7d\x25\0\0\0\0\0\0"));
+    assert_eq!(
+        encode_real(9.9999999999999e99),
+        Ok(*b"\x00\xe3\x99\x99\x99\x99\x99\x99\x99")
+    );
+    // Rounds to 14 digits
+    assert_eq!(
+        encode_real(1.234567890123456),
+        Ok(*b"\x00\x80\x12\x34\x56\x78\x90\x12\x35")
+    );
+    assert_eq!(encode_real(1e-120), Ok(*b"\x00\x80\0\0\0\0\0\0\0"));
+
+    assert_eq!(encode_real(1e100), Err(EncodeError::OutOfRange(1e100)));
+    // Rounds up to 1e100
+    assert_eq!(
+        encode_real(9.99999999999999e99),
+        Err(EncodeError::OutOfRange(9.99999999999999e99))
+    );
+    assert_eq!(
+        encode_real(f64::INFINITY),
+        Err(EncodeError::OutOfRange(f64::INFINITY))
+    );
+    assert!(encode_real(f64::NAN).is_err());
+}
+
+#[test]
+fn encode_round_trips_through_decode() {
+    use crate::read::decode_real;
+
+    for &x in &[
+        1.0,
+        -1.5,
+        0.1,
+        1.0 / 3.0,
+        core::f64::consts::PI,
+        -6.02214076e23,
+        1.602e-19,
+        1e99,
+        -1e-99,
+    ] {
+        let decoded = decode_real(&encode_real(x).unwrap()).unwrap();
+        assert!(
+            ((decoded - x) / x).abs() < 1e-13,
+            "{} round-tripped to {}",
+            x,
+            decoded
+        );
+    }
+}
+
+#[test]
+fn encodes_complex_numbers() {
+    use crate::read::{decode_complex, decode_complex_list};
+
+    let encoded = encode_complex(1.5, -0.25).unwrap();
+    assert_eq!(
+        encoded,
+        *b"\x0c\x80\x15\0\0\0\0\0\0\x8c\x7f\x25\0\0\0\0\0\0"
+    );
+    assert_eq!(decode_complex(&encoded), Ok((1.5, -0.25)));
+    // Zero parts are still flagged as complex
+    assert_eq!(
+        encode_complex(0.0, 0.0).unwrap(),
+        *b"\x0c\x80\0\0\0\0\0\0\0\x0c\x80\0\0\0\0\0\0\0"
+    );
+    assert_eq!(
+        encode_complex(1.0, f64::NAN).unwrap_err().to_string(),
+        "NaN cannot be represented as a TI real number"
+    );
+
+    let list = encode_complex_list(&[(1.0, 2.0), (-3.0, 0.5)]).unwrap();
+    assert_eq!(&list[..2], b"\x02\0");
+    assert_eq!(
+        decode_complex_list(&list),
+        Ok(vec![(1.0, 2.0), (-3.0, 0.5)])
+    );
+    assert_eq!(encode_complex_list(&[]).unwrap(), b"\0\0");
+    assert_eq!(
+        encode_complex_list(&vec![(0.0, 0.0); 65536]),
+        Err(EncodeError::TooManyElements(65536))
+    );
+}
+
+#[test]
+fn windows_round_trip() {
+    use crate::read::decode_window;
+
+    let mut data = [0u8; WINDOW_SIZE];
+    data[0] = 0x5a;
+    for (i, part) in data[1..].chunks_exact_mut(REAL_SIZE).enumerate() {
+        part.copy_from_slice(&encode_real(i as f64 - 1.5).unwrap());
+    }
+    let mut window = decode_window(&data).unwrap();
+    assert_eq!(window.leading_byte, 0x5a);
+    assert_eq!(window.x_min, -1.5);
+    assert_eq!(window.w_n_min_1, 20.5);
+    assert_eq!(encode_window(&window), Ok(data));
+
+    window.theta_step = core::f64::consts::PI / 24.0;
+    let encoded = encode_window(&window).unwrap();
+    let decoded = decode_window(&encoded).unwrap();
+    assert!((decoded.theta_step - window.theta_step).abs() < 1e-13);
+    assert_eq!(decoded.x_min, -1.5);
+
+    window.y_max = f64::INFINITY;
+    assert_eq!(
+        encode_window(&window),
+        Err(EncodeError::OutOfRange(f64::INFINITY))
+    );
+}
+
+#[test]
+fn table_setups_round_trip() {
+    use crate::read::decode_table_setup;
+
+    let setup = TableSetup {
+        tbl_start: -10.0,
+        delta_tbl: 0.5,
+        flags: 1,
+    };
+    let encoded = encode_table_setup(&setup).unwrap();
+    assert_eq!(
+        encoded,
+        *b"\x80\x81\x10\0\0\0\0\0\0\x00\x7f\x50\0\0\0\0\0\0\x01"
+    );
+    assert_eq!(decode_table_setup(&encoded), Ok(setup.clone()));
+
+    let setup = TableSetup {
+        delta_tbl: f64::NAN,
+        ..setup
+    };
+    assert!(encode_table_setup(&setup).is_err());
+}
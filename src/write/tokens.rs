@@ -0,0 +1,142 @@
+//! Conversion of text to tokenized data.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::tokens::TokenTable;
+
+/// Errors encountered when tokenizing text.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum TokenizeError {
+    /// No token matched the text at the given position.
+    ///
+    /// Lines and columns are both counted from 1, and columns are counted in characters.
+    #[error("No token matches the text at line {line}, column {column}")]
+    UnknownToken { line: usize, column: usize },
+}
+
+/// Parse an unknown-token placeholder like `[0xBB6D]` from the beginning of `s`, returning the
+/// token bytes and the length of the placeholder.
+fn parse_placeholder(s: &str) -> Option<(Vec<u8>, usize)> {
+    let digits = s.strip_prefix("[0x")?;
+    let end = digits.find(']')?;
+    let value = u16::from_str_radix(&digits[..end], 16).ok()?;
+    let bytes = match end {
+        2 => vec![value as u8],
+        4 => value.to_be_bytes().to_vec(),
+        _ => return None,
+    };
+    Some((bytes, 3 + end + 1))
+}
+
+/// Convert TI-BASIC source text to tokenized program data.
+///
+/// This is the inverse of [`detokenize`](crate::read::tokens::detokenize). Text is matched
+/// against the token table, preferring the longest token that matches so multi-character tokens
+/// like `Disp ` are recognized before single letters. Where several tokens have the same text
+/// (for instance lowercase letters and some statistics variables), the one with the greatest
+/// value is chosen, which makes single lowercase letters into lowercase letter tokens. Newlines
+/// (`\n` or `\r\n`) become newline tokens, and placeholders like `[0xBB6D]` are converted back to
+/// the token values they represent.
+///
+/// Returns [`TokenizeError::UnknownToken`] with the position of the first text that doesn't
+/// match any token.
+pub fn tokenize(src: &str) -> Result<Vec<u8>, TokenizeError> {
+    tokenize_with(src, TokenTable::builtin())
+}
+
+/// Convert TI-BASIC source text to tokenized program data, using the given token table.
+///
+/// This is like [`tokenize`], but text is matched against the tokens in `table` instead of the
+/// built-in table. Tokens in the table with empty text are never produced.
+pub fn tokenize_with(src: &str, table: &TokenTable) -> Result<Vec<u8>, TokenizeError> {
+    let mut out = Vec::with_capacity(src.len());
+    let mut rest = src;
+    let (mut line, mut column) = (1, 1);
+
+    while !rest.is_empty() {
+        let (bytes, len) = if rest.starts_with("\r\n") {
+            (vec![0x3f], 2)
+        } else if let Some(placeholder) = parse_placeholder(rest) {
+            placeholder
+        } else {
+            // Tokens with no text can't be written, and would match without consuming anything
+            let (value, text) = table
+                .iter()
+                .filter(|(_, text)| !text.is_empty() && rest.starts_with(text))
+                .max_by_key(|&(value, text)| (text.len(), value))
+                .ok_or(TokenizeError::UnknownToken { line, column })?;
+
+            let bytes = if value > 0xff {
+                value.to_be_bytes().to_vec()
+            } else {
+                vec![value as u8]
+            };
+            (bytes, text.len())
+        };
+
+        out.extend_from_slice(&bytes);
+        for c in rest[..len].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else if c != '\r' {
+                column += 1;
+            }
+        }
+        rest = &rest[len..];
+    }
+
+    Ok(out)
+}
+
+#[test]
+fn tokenizes_programs() {
+    assert_eq!(
+        tokenize("Prompt A\nDisp \"HI\",A\r\nA→θ\nStr1").unwrap(),
+        b"\xdd\x41\x3f\xde\x2a\x48\x49\x2a\x2b\x41\x3f\x41\x04\x5b\x3f\xaa\x00"
+    );
+    assert_eq!(
+        tokenize("randInt(1,6)").unwrap(),
+        b"\xbb\x0a\x31\x2b\x36\x11"
+    );
+    assert_eq!(tokenize("abc").unwrap(), b"\xbb\xb0\xbb\xb1\xbb\xb2");
+    assert_eq!(tokenize("[0x26][0xBB6D]").unwrap(), b"\x26\xbb\x6d");
+    assert_eq!(
+        tokenize("Disp A\nA~B"),
+        Err(TokenizeError::UnknownToken { line: 2, column: 2 })
+    );
+}
+
+#[test]
+fn tokenizes_with_custom_tables() {
+    let mut table = TokenTable::default();
+    table.insert(0x04, "->");
+    assert_eq!(tokenize_with("A->B", &table).unwrap(), b"\x41\x04\x42");
+    assert_eq!(
+        tokenize_with("A", &TokenTable::empty()),
+        Err(TokenizeError::UnknownToken { line: 1, column: 1 })
+    );
+}
+
+#[test]
+fn tokenize_inverts_detokenize() {
+    use crate::read::tokens::detokenize;
+
+    let program = b"\xce\x41\x6c\x42\x3f\xcf\x3f\xde\x2a\xbb\xb0\x2a\x3f\xd4\x3f\xbb\x6d";
+    assert_eq!(tokenize(&detokenize(program).unwrap()).unwrap(), program);
+}
+
+#[test]
+fn empty_tokens_are_ignored() {
+    let mut table = TokenTable::default();
+    table.insert(0x01, "");
+    assert_eq!(tokenize_with("AB", &table).unwrap(), b"AB");
+
+    let mut table = TokenTable::empty();
+    table.insert(0x01, "");
+    assert_eq!(
+        tokenize_with("AB", &table),
+        Err(TokenizeError::UnknownToken { line: 1, column: 1 })
+    );
+}